@@ -1,7 +1,7 @@
 extern crate cursive;
 
 use cursive::Cursive;
-use cursive::event::{Event, Key};
+use cursive::event::{Event, Key, Modifiers};
 use cursive::traits::*;
 use cursive::views::{Dialog, EditView, OnEventView, TextArea};
 
@@ -21,7 +21,7 @@ fn main() {
     // We'll add a find feature!
     siv.add_layer(Dialog::info("Hint: press Ctrl-F to find in text!"));
 
-    siv.add_global_callback(Event::CtrlChar('f'), |s| {
+    siv.add_global_callback(Event::ModChar(Modifiers::CTRL, 'f'), |s| {
         // When Ctrl-F is pressed, show the Find popup.
         // Pressing the Escape key will discard it.
         s.add_layer(
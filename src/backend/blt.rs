@@ -1,3 +1,5 @@
+//! Backend using BearLibTerminal, which opens its own window.
+
 extern crate bear_lib_terminal;
 
 use self::bear_lib_terminal::Color as BltColor;
@@ -5,11 +7,18 @@ use self::bear_lib_terminal::geometry::Size;
 use self::bear_lib_terminal::terminal::{self, state, Event as BltEvent,
                                         KeyCode};
 use backend;
-use event::{Event, Key, MouseButton, MouseEvent};
+use event::{Event, Key, Modifiers, MouseButton, MouseEvent};
+use std::cmp::min;
 use std::collections::HashSet;
+use std::time::{Duration, Instant};
 use theme::{BaseColor, Color, ColorPair, Effect};
 use vec::Vec2;
 
+// BearLibTerminal cannot wait for input with a timeout: when one is needed
+// (for timers or a refresh rate), we check for input this often instead
+// (in milliseconds).
+const INPUT_POLL_DELAY: u64 = 10;
+
 enum ColorRole {
     Foreground,
     Background,
@@ -18,6 +27,7 @@ enum ColorRole {
 pub struct Concrete {
     mouse_position: Vec2,
     buttons_pressed: HashSet<MouseButton>,
+    refresh_interval: Option<Duration>,
 }
 
 impl Concrete {
@@ -52,12 +62,16 @@ impl Concrete {
             | KeyCode::Right
             | KeyCode::Left
             | KeyCode::Down
-            | KeyCode::Up => match (shift, ctrl) {
-                (true, true) => Event::CtrlShift(blt_keycode_to_key(kc)),
-                (true, false) => Event::Shift(blt_keycode_to_key(kc)),
-                (false, true) => Event::Ctrl(blt_keycode_to_key(kc)),
-                (false, false) => Event::Key(blt_keycode_to_key(kc)),
-            },
+            | KeyCode::Up => {
+                let mut modifiers = Modifiers::NONE;
+                if shift {
+                    modifiers |= Modifiers::SHIFT;
+                }
+                if ctrl {
+                    modifiers |= Modifiers::CTRL;
+                }
+                Event::key_with(modifiers, blt_keycode_to_key(kc))
+            }
             // TODO: mouse support
             KeyCode::MouseLeft
             | KeyCode::MouseRight
@@ -135,17 +149,21 @@ impl Concrete {
             | KeyCode::Num7
             | KeyCode::Num8
             | KeyCode::Num9
-            | KeyCode::Num0 => if ctrl {
-                Event::CtrlChar(blt_keycode_to_char(kc, shift))
-            } else {
-                Event::Char(blt_keycode_to_char(kc, shift))
-            },
+            | KeyCode::Num0 => {
+                // Shift is already applied to the character.
+                let modifiers = if ctrl {
+                    Modifiers::CTRL
+                } else {
+                    Modifiers::NONE
+                };
+                Event::char_with(modifiers, blt_keycode_to_char(kc, shift))
+            }
         }
     }
 }
 
 impl backend::Backend for Concrete {
-    fn init() -> Box<Self> {
+    fn init() -> Result<Box<Self>, backend::Error> {
         terminal::open("Cursive", 80, 24);
         terminal::set(terminal::config::Window::empty().resizeable(true));
         terminal::set(vec![
@@ -162,15 +180,30 @@ impl backend::Backend for Concrete {
         let c = Concrete {
             mouse_position: Vec2::zero(),
             buttons_pressed: HashSet::new(),
+            refresh_interval: None,
         };
 
-        Box::new(c)
+        Ok(Box::new(c))
     }
 
     fn finish(&mut self) {
         terminal::close();
     }
 
+    fn set_title(&mut self, title: &str) {
+        terminal::set(
+            terminal::config::Window::empty().title(title.to_string()),
+        );
+    }
+
+    fn capabilities(&self) -> backend::Capabilities {
+        backend::Capabilities {
+            effects: Effect::Simple | Effect::Reverse,
+            cursor: false,
+            ..backend::Capabilities::default()
+        }
+    }
+
     fn set_color(&self, color: ColorPair) -> ColorPair {
         let current = ColorPair {
             front: blt_colour_to_colour(state::foreground()),
@@ -241,11 +274,21 @@ impl backend::Backend for Concrete {
         terminal::print_xy(x as i32, y as i32, text);
     }
 
-    fn set_refresh_rate(&mut self, _: u32) {
-        // TODO: unsupported
+    fn set_refresh_rate(&mut self, fps: u32) {
+        self.refresh_interval = if fps == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(1000 / u64::from(fps)))
+        };
     }
 
     fn poll_event(&mut self) -> Event {
+        if let Some(interval) = self.refresh_interval {
+            if !wait_for_input(interval) {
+                return Event::Refresh;
+            }
+        }
+
         // TODO: we could add backend-specific controls here.
         // Ex: ctrl+mouse wheel cause window cellsize to change
         if let Some(ev) = terminal::wait_event() {
@@ -255,14 +298,14 @@ impl backend::Backend for Concrete {
                 // TODO: mouse support
                 BltEvent::MouseMove { x, y } => {
                     self.mouse_position = Vec2::new(x as usize, y as usize);
-                    // TODO: find out if a button is pressed?
-                    match self.buttons_pressed.iter().next() {
-                        None => Event::Refresh,
-                        Some(btn) => Event::Mouse {
-                            event: MouseEvent::Hold(*btn),
-                            position: self.mouse_position,
-                            offset: Vec2::zero(),
-                        },
+                    let event = match self.buttons_pressed.iter().next() {
+                        None => MouseEvent::Move,
+                        Some(btn) => MouseEvent::Hold(*btn),
+                    };
+                    Event::Mouse {
+                        event,
+                        position: self.mouse_position,
+                        offset: Vec2::zero(),
                     }
                 }
                 BltEvent::MouseScroll { delta } => Event::Mouse {
@@ -302,6 +345,39 @@ impl backend::Backend for Concrete {
             Event::Refresh
         }
     }
+
+    fn poll_event_timeout(&mut self, timeout: Duration) -> Event {
+        let timeout = match self.refresh_interval {
+            Some(interval) => min(interval, timeout),
+            None => timeout,
+        };
+
+        if wait_for_input(timeout) {
+            self.poll_event()
+        } else {
+            Event::Refresh
+        }
+    }
+}
+
+/// Waits until some input is available, or until `timeout` elapsed.
+///
+/// Returns `true` if some input is available.
+fn wait_for_input(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if terminal::has_input() {
+            return true;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+
+        let remaining = backend::timeout_ms(deadline - now);
+        terminal::delay(min(remaining, INPUT_POLL_DELAY) as i32);
+    }
 }
 
 fn blt_colour_to_colour(c: BltColor) -> Color {
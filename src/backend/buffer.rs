@@ -0,0 +1,543 @@
+//! Double buffering in front of a backend.
+//!
+//! Prints are recorded in a back buffer. On refresh, this buffer is compared
+//! to what was sent for the previous frame, and only the changed cells are
+//! forwarded to the actual backend.
+
+use backend::{self, Backend, Capabilities, CursorShape, Waker};
+use enumset::EnumSet;
+use event::Event;
+use std::cell::{Cell, RefCell};
+use std::sync::Arc;
+use std::time::Duration;
+use theme::{Color, ColorDepth, ColorPair, Effect};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use vec::Vec2;
+
+/// Style of a single cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct CellStyle {
+    colors: ColorPair,
+    effects: EnumSet<Effect>,
+}
+
+/// A single cell of the screen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ScreenCell {
+    /// Grapheme in this cell.
+    ///
+    /// Empty if this cell is covered by a wide grapheme on its left.
+    text: String,
+    style: CellStyle,
+}
+
+impl ScreenCell {
+    fn blank(color: Color) -> Self {
+        ScreenCell {
+            text: String::from(" "),
+            style: CellStyle {
+                colors: ColorPair {
+                    front: color,
+                    back: color,
+                },
+                effects: EnumSet::new(),
+            },
+        }
+    }
+
+    fn is_continuation(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// Grid of cells.
+struct Frame {
+    size: Vec2,
+    cells: Vec<ScreenCell>,
+}
+
+impl Frame {
+    fn new(size: Vec2) -> Self {
+        Frame {
+            size,
+            cells: vec![
+                ScreenCell::blank(Color::TerminalDefault);
+                size.x * size.y
+            ],
+        }
+    }
+
+    fn fill(&mut self, color: Color) {
+        for cell in &mut self.cells {
+            *cell = ScreenCell::blank(color);
+        }
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.size.x + x
+    }
+
+    /// Replaces the cell at `(x, y)`, taking care of wide graphemes.
+    fn set(&mut self, x: usize, y: usize, cell: ScreenCell) {
+        let i = self.index(x, y);
+
+        // Overwriting half of a wide grapheme leaves the other half blank.
+        if self.cells[i].is_continuation() {
+            let mut lead = x;
+            while lead > 0
+                && self.cells[self.index(lead, y)].is_continuation()
+            {
+                lead -= 1;
+            }
+            let j = self.index(lead, y);
+            self.cells[j].text = String::from(" ");
+        }
+        let mut next = x + 1;
+        while next < self.size.x
+            && self.cells[self.index(next, y)].is_continuation()
+        {
+            let j = self.index(next, y);
+            self.cells[j].text = String::from(" ");
+            next += 1;
+        }
+
+        self.cells[i] = cell;
+    }
+
+    fn print(&mut self, pos: Vec2, text: &str, style: CellStyle) {
+        if pos.y >= self.size.y {
+            return;
+        }
+
+        let mut x = pos.x;
+        for grapheme in text.graphemes(true) {
+            let width = grapheme.width();
+            if x + width > self.size.x {
+                break;
+            }
+
+            self.set(
+                x,
+                pos.y,
+                ScreenCell {
+                    text: String::from(grapheme),
+                    style,
+                },
+            );
+            for i in 1..width {
+                self.set(
+                    x + i,
+                    pos.y,
+                    ScreenCell {
+                        text: String::new(),
+                        style,
+                    },
+                );
+            }
+
+            x += width;
+        }
+    }
+}
+
+/// Backend wrapper only sending changes to the wrapped backend.
+///
+/// `Cursive` automatically puts one in front of its backend.
+pub struct Buffered {
+    backend: Box<Backend>,
+
+    /// Frame currently being drawn.
+    back: RefCell<Frame>,
+    /// Frame last sent to the backend.
+    front: RefCell<Frame>,
+    /// `false` if we don't know what the backend is currently showing.
+    front_valid: Cell<bool>,
+    /// Set when the screen was cleared during this frame.
+    cleared: Cell<Option<Color>>,
+
+    current_style: Cell<CellStyle>,
+    /// Cursor requested during this frame.
+    cursor: Cell<Option<((usize, usize), CursorShape)>>,
+}
+
+impl Buffered {
+    /// Wraps the given backend.
+    pub fn new(backend: Box<Backend>) -> Self {
+        let (x, y) = backend.screen_size();
+        let size = Vec2::new(x, y);
+
+        Buffered {
+            backend,
+            back: RefCell::new(Frame::new(size)),
+            front: RefCell::new(Frame::new(size)),
+            front_valid: Cell::new(false),
+            cleared: Cell::new(None),
+            current_style: Cell::new(CellStyle {
+                colors: ColorPair::from_256colors(0, 0),
+                effects: EnumSet::new(),
+            }),
+            cursor: Cell::new(None),
+        }
+    }
+
+    /// Makes sure the buffers have the same size as the screen.
+    ///
+    /// Asking the backend for its size can be expensive, so this is only
+    /// done when the screen is cleared or resized, not on every print.
+    fn check_size(&self) {
+        let (x, y) = self.backend.screen_size();
+        let size = Vec2::new(x, y);
+
+        if self.back.borrow().size != size {
+            *self.back.borrow_mut() = Frame::new(size);
+            *self.front.borrow_mut() = Frame::new(size);
+            self.front_valid.set(false);
+        }
+    }
+
+    /// Follows the size of the screen when it changes.
+    fn on_event(&self, event: &Event) {
+        if *event == Event::WindowResize {
+            self.check_size();
+        }
+    }
+
+    /// Sends the given style to the backend.
+    ///
+    /// Colors are downgraded, and unsupported effects are dropped,
+    /// according to `capabilities`.
+    /// `active` holds the effects currently enabled on the backend.
+    fn apply_style(
+        &self, style: CellStyle, capabilities: &Capabilities,
+        active: &mut EnumSet<Effect>,
+    ) {
+        let depth = capabilities.color_depth;
+        self.backend.set_color(ColorPair {
+            front: style.colors.front.downgrade(depth),
+            back: style.colors.back.downgrade(depth),
+        });
+
+        let effects = style.effects.intersection(capabilities.effects);
+        for effect in active.difference(effects).iter() {
+            self.backend.unset_effect(effect);
+        }
+        for effect in effects.difference(*active).iter() {
+            self.backend.set_effect(effect);
+        }
+        *active = effects;
+    }
+
+    /// Sends every changed cell to the backend.
+    fn flush(&self) {
+        let back = self.back.borrow();
+        let mut front = self.front.borrow_mut();
+
+        let capabilities = self.backend.capabilities();
+        let mut front_valid = self.front_valid.get();
+        if let Some(color) = self.cleared.take() {
+            self.backend.clear(color.downgrade(capabilities.color_depth));
+            front.fill(color);
+            front_valid = true;
+        }
+
+        let mut active = EnumSet::new();
+        let mut text = String::new();
+
+        for y in 0..back.size.y {
+            let mut x = 0;
+            while x < back.size.x {
+                let i = back.index(x, y);
+                if front_valid && back.cells[i] == front.cells[i] {
+                    x += 1;
+                    continue;
+                }
+
+                // Collect a run of changed cells with the same style.
+                let start = x;
+                let style = back.cells[i].style;
+                text.clear();
+                while x < back.size.x {
+                    let i = back.index(x, y);
+                    let cell = &back.cells[i];
+                    if cell.style != style
+                        || (front_valid && *cell == front.cells[i])
+                    {
+                        break;
+                    }
+                    text.push_str(&cell.text);
+                    x += 1;
+                }
+
+                if !text.is_empty() {
+                    self.apply_style(style, &capabilities, &mut active);
+                    self.backend.print_at((start, y), &text);
+                }
+            }
+        }
+
+        for effect in active.iter() {
+            self.backend.unset_effect(effect);
+        }
+
+        front.cells.clone_from(&back.cells);
+        self.front_valid.set(true);
+    }
+}
+
+impl Backend for Buffered {
+    fn init() -> Result<Box<Self>, backend::Error> {
+        let backend = backend::init_default()?;
+        Ok(Box::new(Buffered::new(backend)))
+    }
+
+    fn finish(&mut self) {
+        self.backend.finish();
+    }
+
+    fn refresh(&mut self) {
+        self.flush();
+        // The cursor is placed last, since printing moves it.
+        // Views ask for it again on every frame.
+        match self.cursor.take() {
+            Some((position, shape)) => {
+                self.backend.show_cursor(position, shape)
+            }
+            None => self.backend.hide_cursor(),
+        }
+        self.backend.refresh();
+    }
+
+    fn has_colors(&self) -> bool {
+        self.backend.has_colors()
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.backend.color_depth()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        self.backend.screen_size()
+    }
+
+    fn poll_event(&mut self) -> Event {
+        let event = self.backend.poll_event();
+        self.on_event(&event);
+        event
+    }
+
+    fn poll_event_timeout(&mut self, timeout: Duration) -> Event {
+        let event = self.backend.poll_event_timeout(timeout);
+        self.on_event(&event);
+        event
+    }
+
+    fn waker(&self) -> Option<Arc<Waker>> {
+        self.backend.waker()
+    }
+
+    fn suspend(&mut self) {
+        self.backend.suspend();
+    }
+
+    fn resume(&mut self) {
+        self.backend.resume();
+        // Someone else used the terminal: we don't know what it shows.
+        self.front_valid.set(false);
+    }
+
+    fn stop_process(&mut self) {
+        self.backend.stop_process();
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.backend.set_title(title);
+    }
+
+    fn bell(&mut self) {
+        self.backend.bell();
+    }
+
+    fn flash(&mut self) {
+        self.backend.flash();
+    }
+
+    fn set_clipboard(&mut self, text: &str) {
+        self.backend.set_clipboard(text);
+    }
+
+    fn print_at(&self, (x, y): (usize, usize), text: &str) {
+        self.back
+            .borrow_mut()
+            .print(Vec2::new(x, y), text, self.current_style.get());
+    }
+
+    fn show_cursor(&self, position: (usize, usize), shape: CursorShape) {
+        self.cursor.set(Some((position, shape)));
+    }
+
+    fn hide_cursor(&self) {
+        self.cursor.set(None);
+    }
+
+    fn clear(&self, color: Color) {
+        self.check_size();
+        self.back.borrow_mut().fill(color);
+        self.cleared.set(Some(color));
+    }
+
+    fn set_refresh_rate(&mut self, fps: u32) {
+        self.backend.set_refresh_rate(fps);
+    }
+
+    fn set_color(&self, colors: ColorPair) -> ColorPair {
+        let mut style = self.current_style.get();
+        let previous = style.colors;
+        style.colors = colors;
+        self.current_style.set(style);
+        previous
+    }
+
+    fn set_effect(&self, effect: Effect) {
+        if effect == Effect::Simple {
+            return;
+        }
+        let mut style = self.current_style.get();
+        style.effects.insert(effect);
+        self.current_style.set(style);
+    }
+
+    fn unset_effect(&self, effect: Effect) {
+        let mut style = self.current_style.get();
+        style.effects.remove(effect);
+        self.current_style.set(style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use std::rc::Rc;
+
+    /// Backend recording every print it receives.
+    struct Recorder {
+        size: Vec2,
+        prints: Rc<RefCell<Vec<(usize, usize, String)>>>,
+        /// Number of calls to `screen_size`.
+        size_queries: Rc<Cell<usize>>,
+    }
+
+    impl Backend for Recorder {
+        fn init() -> Result<Box<Self>, backend::Error> {
+            Ok(Box::new(Recorder {
+                size: Vec2::new(80, 24),
+                prints: Rc::new(RefCell::new(Vec::new())),
+                size_queries: Rc::new(Cell::new(0)),
+            }))
+        }
+        fn finish(&mut self) {}
+        fn refresh(&mut self) {}
+        fn has_colors(&self) -> bool {
+            true
+        }
+        fn screen_size(&self) -> (usize, usize) {
+            self.size_queries.set(self.size_queries.get() + 1);
+            (self.size.x, self.size.y)
+        }
+        fn poll_event(&mut self) -> Event {
+            Event::WindowResize
+        }
+        fn print_at(&self, (x, y): (usize, usize), text: &str) {
+            self.prints.borrow_mut().push((x, y, String::from(text)));
+        }
+        fn clear(&self, _: Color) {}
+        fn set_refresh_rate(&mut self, _: u32) {}
+        fn set_color(&self, colors: ColorPair) -> ColorPair {
+            colors
+        }
+        fn set_effect(&self, _: Effect) {}
+        fn unset_effect(&self, _: Effect) {}
+    }
+
+    #[test]
+    fn only_sends_changes() {
+        let prints = Rc::new(RefCell::new(Vec::new()));
+        let mut buffered = Buffered::new(Box::new(Recorder {
+            size: Vec2::new(10, 2),
+            prints: Rc::clone(&prints),
+            size_queries: Rc::new(Cell::new(0)),
+        }));
+
+        buffered.clear(Color::TerminalDefault);
+        buffered.print_at((0, 0), "Hello");
+        buffered.print_at((0, 1), "World");
+        buffered.refresh();
+        assert_eq!(
+            *prints.borrow(),
+            vec![(0, 0, "Hello".to_string()), (0, 1, "World".to_string())]
+        );
+
+        prints.borrow_mut().clear();
+        buffered.print_at((0, 0), "Hello");
+        buffered.print_at((0, 1), "Wombat");
+        buffered.refresh();
+        assert_eq!(*prints.borrow(), vec![(2, 1, "mbat".to_string())]);
+
+        prints.borrow_mut().clear();
+        buffered.refresh();
+        assert!(prints.borrow().is_empty());
+    }
+
+    #[test]
+    fn checks_size_once_per_frame() {
+        let size_queries = Rc::new(Cell::new(0));
+        let mut buffered = Buffered::new(Box::new(Recorder {
+            size: Vec2::new(10, 2),
+            prints: Rc::new(RefCell::new(Vec::new())),
+            size_queries: Rc::clone(&size_queries),
+        }));
+
+        size_queries.set(0);
+        buffered.clear(Color::TerminalDefault);
+        for x in 0..10 {
+            buffered.print_at((x, 0), "a");
+        }
+        buffered.refresh();
+        assert_eq!(size_queries.get(), 1);
+
+        size_queries.set(0);
+        buffered.print_at((0, 1), "b");
+        buffered.refresh();
+        assert_eq!(size_queries.get(), 0);
+
+        assert_eq!(buffered.poll_event(), Event::WindowResize);
+        assert_eq!(size_queries.get(), 1);
+    }
+
+    #[test]
+    fn keeps_styles() {
+        let puppet = Puppet::new((4, 1));
+        let screen = puppet.screen();
+        let mut buffered = Buffered::new(puppet);
+
+        let red = ColorPair::from_256colors(1, 0);
+        buffered.clear(Color::TerminalDefault);
+        buffered.set_color(red);
+        buffered.set_effect(Effect::Bold);
+        buffered.print_at((1, 0), "ab");
+        buffered.unset_effect(Effect::Bold);
+        buffered.refresh();
+
+        let screen = screen.borrow();
+        assert_eq!(screen.line(0), " ab ");
+        let cell = screen.cell((1, 0)).unwrap();
+        assert_eq!(cell.colors, red);
+        assert!(cell.effects.contains(Effect::Bold));
+        assert!(screen.cell((3, 0)).unwrap().effects.is_empty());
+    }
+}
@@ -0,0 +1,56 @@
+use enumset::EnumSet;
+use theme::{ColorDepth, Effect};
+
+/// Features supported by a backend.
+///
+/// Returned by [`Backend::capabilities`], and available to views while
+/// drawing with [`Printer::capabilities`].
+///
+/// [`Backend::capabilities`]: trait.Backend.html#method.capabilities
+/// [`Printer::capabilities`]: ../struct.Printer.html#method.capabilities
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Colors that can be shown.
+    pub color_depth: ColorDepth,
+    /// `true` if mouse events can be sent.
+    pub mouse: bool,
+    /// Effects that can be shown.
+    ///
+    /// Other effects are ignored.
+    pub effects: EnumSet<Effect>,
+    /// `true` if unicode box-drawing characters (like `┌` or `─`)
+    /// can be shown.
+    ///
+    /// Otherwise, borders are drawn with ASCII characters.
+    pub box_drawing: bool,
+    /// `true` if the cursor can be shown, hidden and moved.
+    pub cursor: bool,
+}
+
+impl Default for Capabilities {
+    /// Returns capabilities with every feature supported.
+    fn default() -> Self {
+        Capabilities {
+            color_depth: ColorDepth::TrueColor,
+            mouse: true,
+            effects: EnumSet::all(),
+            box_drawing: true,
+            cursor: true,
+        }
+    }
+}
+
+/// Checks the locale environment variables for an UTF-8 encoding.
+///
+/// Returns `None` if no locale is set.
+#[cfg(any(feature = "termion", feature = "pancurses"))]
+pub(super) fn utf8_locale() -> Option<bool> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| ::std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value = value.to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+}
@@ -1,16 +1,79 @@
+//! Backends based on the curses library.
+
+#[cfg(unix)]
+extern crate libc;
+
+use backend::{self, xterm_modifiers};
 use event::{Event, Key};
 use std::collections::HashMap;
-use theme::{BaseColor, Color};
+use std::time::Duration;
+use theme::Color;
 
 #[cfg(feature = "ncurses")]
-mod n;
-#[cfg(feature = "ncurses")]
-pub use self::n::*;
+pub mod n;
 
 #[cfg(feature = "pancurses")]
-mod pan;
-#[cfg(feature = "pancurses")]
-pub use self::pan::*;
+pub mod pan;
+
+/// Returns the shortest of a curses timeout and a duration.
+///
+/// Curses timeouts are in milliseconds, with `-1` meaning "forever".
+fn shortest_timeout(current: i32, timeout: Duration) -> i32 {
+    let timeout = backend::timeout_ms(timeout).min(i32::max_value() as u64);
+    let timeout = timeout as i32;
+    if current < 0 {
+        timeout
+    } else {
+        current.min(timeout)
+    }
+}
+
+/// Lets Ctrl-Z through as a regular key.
+///
+/// Otherwise, the terminal sends SIGTSTP instead, and the curses handler
+/// stops the process with mouse tracking and bracketed paste still
+/// enabled. `Cursive` handles the key by suspending the process properly.
+///
+/// Only the current terminal mode is changed: callers need to save it with
+/// `def_prog_mode`. The shell mode, restored by `endwin`, is untouched.
+#[cfg(unix)]
+fn disable_suspend_key() {
+    unsafe {
+        let mut termios: libc::termios = ::std::mem::zeroed();
+        if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0 {
+            termios.c_cc[libc::VSUSP] = libc::_POSIX_VDISABLE as libc::cc_t;
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+        }
+    }
+}
+
+/// Starts curses with `newterm`, for the terminal type from `TERM`.
+///
+/// Unlike `initscr`, `newterm` doesn't exit the process when the terminal
+/// is not supported: a missing `TERM`, or a null screen returned by
+/// `newterm`, is reported as an unknown terminal instead.
+#[cfg(unix)]
+fn new_screen<S, F>(
+    term: Option<String>, newterm: F
+) -> Result<*mut S, backend::Error>
+where
+    F: FnOnce(&str) -> *mut S,
+{
+    let term = match term {
+        Some(term) => term,
+        None => return Err(backend::Error::UnknownTerminal(String::new())),
+    };
+    if term.is_empty() {
+        return Err(backend::Error::UnknownTerminal(term));
+    }
+
+    let screen = newterm(&term);
+    if screen.is_null() {
+        Err(backend::Error::UnknownTerminal(term))
+    } else {
+        Ok(screen)
+    }
+}
 
 fn split_i32(code: i32) -> Vec<u8> {
     (0..4).map(|i| ((code >> (8 * i)) & 0xFF) as u8).collect()
@@ -39,6 +102,19 @@ where
             None => continue,
         };
 
+        // Some terminfo entries describe focus reports as keys.
+        match name.as_str() {
+            "kxIN" => {
+                target.insert(code, Event::FocusIn);
+                continue;
+            }
+            "kxOUT" => {
+                target.insert(code, Event::FocusOut);
+                continue;
+            }
+            _ => (),
+        }
+
         if !name.starts_with('k') {
             continue;
         }
@@ -48,52 +124,61 @@ where
             Some(&key) => key,
             None => continue,
         };
-        let event = match modifier {
-            "3" => Event::Alt(key),
-            "4" => Event::AltShift(key),
-            "5" => Event::Ctrl(key),
-            "6" => Event::CtrlShift(key),
-            "7" => Event::CtrlAlt(key),
-            _ => continue,
+        let modifiers = match modifier.parse().ok().and_then(xterm_modifiers)
+        {
+            Some(modifiers) => modifiers,
+            None => continue,
         };
-        target.insert(code, event);
+        target.insert(code, Event::ModKey(modifiers, key));
     }
 }
 
 fn find_closest(color: &Color) -> i16 {
-    match *color {
-        Color::TerminalDefault => -1,
-        Color::Dark(BaseColor::Black) => 0,
-        Color::Dark(BaseColor::Red) => 1,
-        Color::Dark(BaseColor::Green) => 2,
-        Color::Dark(BaseColor::Yellow) => 3,
-        Color::Dark(BaseColor::Blue) => 4,
-        Color::Dark(BaseColor::Magenta) => 5,
-        Color::Dark(BaseColor::Cyan) => 6,
-        Color::Dark(BaseColor::White) => 7,
-        Color::Light(BaseColor::Black) => 8,
-        Color::Light(BaseColor::Red) => 9,
-        Color::Light(BaseColor::Green) => 10,
-        Color::Light(BaseColor::Yellow) => 11,
-        Color::Light(BaseColor::Blue) => 12,
-        Color::Light(BaseColor::Magenta) => 13,
-        Color::Light(BaseColor::Cyan) => 14,
-        Color::Light(BaseColor::White) => 15,
-        Color::Rgb(r, g, b) => {
-            // If r = g = b, it may be a grayscale value!
-            if r == g && g == b && r != 0 && r < 250 {
-                // (r = g = b) = 8 + 10 * n
-                // (r - 8) / 10 = n
-                //
-                let n = (r - 8) / 10;
-                (232 + n) as i16
-            } else {
-                let r = 6 * u16::from(r) / 256;
-                let g = 6 * u16::from(g) / 256;
-                let b = 6 * u16::from(b) / 256;
-                (16 + 36 * r + 6 * g + b) as i16
-            }
+    color.to_256colors().map_or(-1, i16::from)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    fn unknown_terminal(
+        result: Result<*mut i8, backend::Error>
+    ) -> Option<String> {
+        match result {
+            Err(backend::Error::UnknownTerminal(term)) => Some(term),
+            _ => None,
         }
-        Color::RgbLowRes(r, g, b) => i16::from(16 + 36 * r + 6 * g + b),
+    }
+
+    #[test]
+    fn missing_terminal() {
+        let result = new_screen(None, |_| -> *mut i8 {
+            panic!("curses was started without TERM")
+        });
+        assert_eq!(unknown_terminal(result), Some(String::new()));
+
+        let result = new_screen(Some(String::new()), |_| -> *mut i8 {
+            panic!("curses was started without TERM")
+        });
+        assert_eq!(unknown_terminal(result), Some(String::new()));
+    }
+
+    #[test]
+    fn unknown_terminal_type() {
+        let result = new_screen(Some("cursive-unknown".to_string()), |term| {
+            assert_eq!(term, "cursive-unknown");
+            ptr::null_mut()
+        });
+        assert_eq!(
+            unknown_terminal(result),
+            Some("cursive-unknown".to_string())
+        );
+
+        let mut screen = 0i8;
+        let result = new_screen(Some("xterm".to_string()), |_| {
+            &mut screen as *mut i8
+        });
+        assert!(result.is_ok());
     }
 }
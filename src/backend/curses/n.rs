@@ -1,13 +1,21 @@
+//! Backend using the ncurses library.
+
+extern crate libc;
 extern crate ncurses;
 
 use self::ncurses::mmask_t;
-use self::super::{find_closest, split_i32};
+use self::super::{disable_suspend_key, find_closest, new_screen,
+                  shortest_timeout, split_i32};
 use backend;
-use event::{Event, Key, MouseButton, MouseEvent};
+use enumset::EnumSet;
+use event::{Event, Key, Modifiers, MouseButton, MouseEvent};
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
-use std::io::{stdout, Write};
-use theme::{Color, ColorPair, Effect};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::CStr;
+use std::io::{self, stdout, Write};
+use std::sync::Arc;
+use std::time::Duration;
+use theme::{Color, ColorDepth, ColorPair, Effect};
 use utf8;
 use vec::Vec2;
 
@@ -18,7 +26,49 @@ pub struct Concrete {
     key_codes: HashMap<i32, Event>,
 
     last_mouse_button: Option<MouseButton>,
-    event_queue: Vec<Event>,
+    // Events already parsed, waiting to be returned.
+    event_queue: VecDeque<Event>,
+
+    // Timeout for `poll_event`, in milliseconds. -1 to wait forever.
+    timeout: i32,
+    // Read end of the self-pipe used to interrupt `poll_event`.
+    wake_fd: libc::c_int,
+    waker: Arc<PipeWaker>,
+
+    capabilities: backend::Capabilities,
+    // Cursor shape last sent to the terminal, if any.
+    cursor_shape: Cell<Option<backend::CursorShape>>,
+}
+
+/// Wakes up `poll_event` by writing to a pipe.
+struct PipeWaker {
+    fd: libc::c_int,
+}
+
+impl backend::Waker for PipeWaker {
+    fn wake(&self) {
+        let byte = 0u8;
+        // If the pipe is full, a wake-up is already pending anyway.
+        unsafe {
+            libc::write(self.fd, &byte as *const u8 as *const libc::c_void, 1);
+        }
+    }
+}
+
+impl Drop for PipeWaker {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Result of waiting for input.
+enum Wait {
+    /// Input is available on stdin.
+    Input,
+    /// We were interrupted, or the timeout expired.
+    Interrupted,
 }
 
 impl Concrete {
@@ -96,12 +146,11 @@ impl Concrete {
             };
 
             if mevent.bstate == ncurses::REPORT_MOUSE_POSITION as mmask_t {
-                // The event is either a mouse drag event,
-                // or a weird double-release event. :S
-                self.last_mouse_button
-                    .map(MouseEvent::Hold)
-                    .map(&make_event)
-                    .unwrap_or_else(|| Event::Unknown(vec![]))
+                // The mouse moved: this is a drag if a button is held.
+                make_event(
+                    self.last_mouse_button
+                        .map_or(MouseEvent::Move, MouseEvent::Hold),
+                )
             } else {
                 // Identify the button
                 let mut bare_event = mevent.bstate & ((1 << 25) - 1);
@@ -116,13 +165,19 @@ impl Concrete {
                         if event.is_none() {
                             event = Some(e);
                         } else {
-                            self.event_queue.push(make_event(e));
+                            self.event_queue.push_back(make_event(e));
                         }
                     });
                 }
                 if let Some(event) = event {
-                    if let Some(btn) = event.button() {
-                        self.last_mouse_button = Some(btn);
+                    match event {
+                        MouseEvent::Press(btn) => {
+                            self.last_mouse_button = Some(btn)
+                        }
+                        MouseEvent::Release(_) => {
+                            self.last_mouse_button = None
+                        }
+                        _ => (),
                     }
                     make_event(event)
                 } else {
@@ -136,6 +191,185 @@ impl Concrete {
         }
     }
 
+    /// Parses the key following an escape character.
+    ///
+    /// Returns `Key::Esc` if nothing follows.
+    fn parse_alt(&mut self) -> Event {
+        ncurses::timeout(0);
+        let ch = ncurses::getch();
+        ncurses::timeout(-1);
+
+        match ch {
+            -1 => Event::Key(Key::Esc),
+            9 => Event::ModKey(Modifiers::ALT, Key::Tab),
+            10 => Event::ModKey(Modifiers::ALT, Key::Enter),
+            127 => Event::ModKey(Modifiers::ALT, Key::Backspace),
+            1..=26 => {
+                let c = (b'a' - 1 + ch as u8) as char;
+                Event::ModChar(Modifiers::CTRL_ALT, c)
+            }
+            // An escape sequence ncurses didn't recognize.
+            91 => self.parse_csi(),
+            32..=255 => {
+                utf8::read_char(ch as u8, || Some(ncurses::getch() as u8))
+                    .map(|c| Event::ModChar(Modifiers::ALT, c))
+                    .unwrap_or_else(|e| {
+                        warn!("Error reading input: {}", e);
+                        Event::Unknown(vec![27, ch as u8])
+                    })
+            }
+            _ => {
+                // Keep this key for the next call.
+                ncurses::ungetch(ch);
+                Event::Key(Key::Esc)
+            }
+        }
+    }
+
+    /// Parses a control sequence, after `ESC [`.
+    ///
+    /// ncurses only knows about keys, so we handle bracketed paste and
+    /// focus reports here.
+    fn parse_csi(&mut self) -> Event {
+        let mut sequence = vec![27, b'['];
+
+        ncurses::timeout(0);
+        loop {
+            let ch = ncurses::getch();
+            if ch < 0 || ch > 255 {
+                if ch > 255 {
+                    ncurses::ungetch(ch);
+                }
+                break;
+            }
+
+            sequence.push(ch as u8);
+            // Control sequences end with a byte in `@..~`.
+            if 0x40 <= ch && ch <= 0x7E {
+                break;
+            }
+        }
+        ncurses::timeout(-1);
+
+        if sequence.len() == 2 {
+            // Nothing after the `[`: this was Alt+[.
+            Event::ModChar(Modifiers::ALT, '[')
+        } else if sequence == backend::PASTE_START {
+            self.read_paste()
+        } else if sequence == backend::FOCUS_IN {
+            Event::FocusIn
+        } else if sequence == backend::FOCUS_OUT {
+            Event::FocusOut
+        } else {
+            Event::Unknown(sequence)
+        }
+    }
+
+    /// Reads pasted text, until the end of the bracketed paste.
+    fn read_paste(&mut self) -> Event {
+        let mut bytes = Vec::new();
+
+        ncurses::timeout(backend::PASTE_TIMEOUT as i32);
+        loop {
+            let ch = ncurses::getch();
+            if ch < 0 {
+                // The end of the paste never came.
+                break;
+            }
+            if ch > 255 {
+                // ncurses recognized a key in there. Just skip it.
+                continue;
+            }
+
+            bytes.push(ch as u8);
+            if bytes.ends_with(backend::PASTE_END) {
+                let len = bytes.len() - backend::PASTE_END.len();
+                bytes.truncate(len);
+                break;
+            }
+        }
+        ncurses::timeout(-1);
+
+        let text = String::from_utf8_lossy(&bytes)
+            .replace("\r\n", "\n")
+            .replace('\r', "\n");
+        Event::Paste(text)
+    }
+
+    /// Returns the next event, waiting at most `timeout` milliseconds.
+    fn poll_event_within(&mut self, timeout: i32) -> Event {
+        self.event_queue.pop_front().unwrap_or_else(|| {
+            // ncurses may already have some input buffered.
+            ncurses::timeout(0);
+            let ch: i32 = ncurses::getch();
+            ncurses::timeout(-1);
+
+            let ch = if ch != -1 {
+                ch
+            } else {
+                match self.wait_for_input(timeout) {
+                    Wait::Input => ncurses::getch(),
+                    Wait::Interrupted => return Event::Refresh,
+                }
+            };
+
+            // An escape followed by another key means Alt was held.
+            if ch == 27 {
+                return self.parse_alt();
+            }
+
+            // Is it a UTF-8 starting point?
+            if 32 <= ch && ch <= 255 && ch != 127 {
+                utf8::read_char(ch as u8, || Some(ncurses::getch() as u8))
+                    .map(Event::Char)
+                    .unwrap_or_else(|e| {
+                        warn!("Error reading input: {}", e);
+                        Event::Unknown(vec![ch as u8])
+                    })
+            } else {
+                self.parse_ncurses_char(ch)
+            }
+        })
+    }
+
+    /// Waits until stdin is readable, the waker is used or the timeout
+    /// (in milliseconds, `-1` for none) expires.
+    fn wait_for_input(&self, timeout: i32) -> Wait {
+        let mut fds = [
+            libc::pollfd {
+                fd: libc::STDIN_FILENO,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: self.wake_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+
+        let n = unsafe { libc::poll(fds.as_mut_ptr(), 2, timeout) };
+
+        if n > 0 && fds[1].revents != 0 {
+            // Drain the pipe, so the next poll blocks again.
+            let mut buffer = [0u8; 64];
+            while unsafe {
+                libc::read(
+                    self.wake_fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            } > 0
+            {}
+            Wait::Interrupted
+        } else if n > 0 {
+            Wait::Input
+        } else {
+            // Timeout, or a signal (like SIGWINCH) was received.
+            Wait::Interrupted
+        }
+    }
+
     fn parse_ncurses_char(&mut self, ch: i32) -> Event {
         // eprintln!("Found {:?}", ncurses::keyname(ch));
         if ch == ncurses::KEY_MOUSE {
@@ -150,7 +384,27 @@ impl Concrete {
 }
 
 impl backend::Backend for Concrete {
-    fn init() -> Box<Self> {
+    fn init() -> Result<Box<Self>, backend::Error> {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0
+                || libc::isatty(libc::STDOUT_FILENO) == 0
+            {
+                return Err(backend::Error::NotATty);
+            }
+        }
+
+        // Self-pipe, used to wake up `poll_event` from other threads.
+        let mut pipe = [0; 2];
+        unsafe {
+            if libc::pipe(pipe.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            for &fd in &pipe {
+                let flags = libc::fcntl(fd, libc::F_GETFL);
+                libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+            }
+        }
+
         // Change the locale.
         // For some reasons it's mandatory to get some UTF-8 support.
         ncurses::setlocale(ncurses::LcCategory::all, "");
@@ -160,7 +414,21 @@ impl backend::Backend for Concrete {
         // Default delay is way too long. 25 is imperceptible yet works fine.
         ::std::env::set_var("ESCDELAY", "25");
 
-        ncurses::initscr();
+        // The streams are never closed, since they wrap stdin and stdout.
+        let screen = new_screen(::std::env::var("TERM").ok(), |term| unsafe {
+            let output =
+                libc::fdopen(libc::STDOUT_FILENO, b"w\0".as_ptr() as _);
+            let input =
+                libc::fdopen(libc::STDIN_FILENO, b"r\0".as_ptr() as _);
+            ncurses::newterm(Some(term), output as _, input as _)
+        });
+        if let Err(e) = screen {
+            unsafe {
+                libc::close(pipe[0]);
+                libc::close(pipe[1]);
+            }
+            return Err(e);
+        }
         ncurses::keypad(ncurses::stdscr(), true);
 
         // This disables mouse click detection,
@@ -174,16 +442,22 @@ impl backend::Backend for Concrete {
         );
         ncurses::noecho();
         ncurses::cbreak();
+        disable_suspend_key();
+        ncurses::def_prog_mode();
         ncurses::start_color();
         // Pick up background and text color from the terminal theme.
         ncurses::use_default_colors();
         // No cursor
         ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE);
 
-        // This asks the terminal to provide us with mouse drag events
-        // (Mouse move when a button is pressed).
-        // Replacing 1002 with 1003 would give us ANY mouse move.
-        print!("\x1B[?1002h");
+        // This asks the terminal to provide us with any mouse move,
+        // with or without a button pressed.
+        print!("\x1B[?1003h");
+        // Pasted text will be surrounded by markers, so we can get it
+        // all at once.
+        print!("\x1B[?2004h");
+        // Tell us when the terminal gains or loses focus.
+        print!("\x1B[?1004h");
         stdout().flush().expect("could not flush stdout");
 
         let c = Concrete {
@@ -191,12 +465,19 @@ impl backend::Backend for Concrete {
             pairs: RefCell::new(HashMap::new()),
 
             last_mouse_button: None,
-            event_queue: Vec::new(),
+            event_queue: VecDeque::new(),
+
+            timeout: -1,
+            wake_fd: pipe[0],
+            waker: Arc::new(PipeWaker { fd: pipe[1] }),
 
             key_codes: initialize_keymap(),
+
+            capabilities: detect_capabilities(),
+            cursor_shape: Cell::new(None),
         };
 
-        Box::new(c)
+        Ok(Box::new(c))
     }
 
     fn screen_size(&self) -> (usize, usize) {
@@ -210,12 +491,67 @@ impl backend::Backend for Concrete {
         ncurses::has_colors()
     }
 
+    fn color_depth(&self) -> ColorDepth {
+        self.capabilities.color_depth
+    }
+
+    fn capabilities(&self) -> backend::Capabilities {
+        self.capabilities.clone()
+    }
+
     fn finish(&mut self) {
-        print!("\x1B[?1002l");
+        self.suspend();
+    }
+
+    fn suspend(&mut self) {
+        if self.cursor_shape.take().is_some() {
+            print!("{}", backend::CURSOR_SHAPE_RESET);
+        }
+        print!("\x1B[?1003l");
+        print!("\x1B[?2004l");
+        print!("\x1B[?1004l");
         stdout().flush().expect("could not flush stdout");
         ncurses::endwin();
     }
 
+    fn resume(&mut self) {
+        // Refreshing after `endwin` brings back the curses mode.
+        ncurses::refresh();
+        ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+        print!("\x1B[?1003h");
+        print!("\x1B[?2004h");
+        print!("\x1B[?1004h");
+        stdout().flush().expect("could not flush stdout");
+    }
+
+    fn stop_process(&mut self) {
+        // ncurses has its own SIGTSTP handler, which would take the
+        // terminal back by itself. We already did what it would do.
+        unsafe {
+            let handler = libc::signal(libc::SIGTSTP, libc::SIG_DFL);
+            libc::raise(libc::SIGTSTP);
+            libc::signal(libc::SIGTSTP, handler);
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        print!("{}", backend::xterm_title(title));
+        stdout().flush().expect("could not flush stdout");
+    }
+
+    fn bell(&mut self) {
+        ncurses::beep();
+    }
+
+    fn flash(&mut self) {
+        ncurses::flash();
+    }
+
+    fn set_clipboard(&mut self, text: &str) {
+        print!("{}", backend::osc52_copy(text));
+        stdout().flush().expect("could not flush stdout");
+    }
+
     fn set_color(&self, colors: ColorPair) -> ColorPair {
         // eprintln!("Color used: {:?}", colors);
         let current = self.current_style.get();
@@ -265,29 +601,50 @@ impl backend::Backend for Concrete {
         ncurses::mvaddstr(y as i32, x as i32, text);
     }
 
+    fn show_cursor(
+        &self, (x, y): (usize, usize), shape: backend::CursorShape
+    ) {
+        if self.cursor_shape.get() != Some(shape) {
+            print!("{}", shape.xterm_sequence());
+            stdout().flush().expect("could not flush stdout");
+            self.cursor_shape.set(Some(shape));
+        }
+        // `refresh` leaves the cursor where the last move left it.
+        ncurses::mv(y as i32, x as i32);
+        ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_VISIBLE);
+    }
+
+    fn hide_cursor(&self) {
+        ncurses::curs_set(ncurses::CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    }
+
     fn poll_event(&mut self) -> Event {
-        self.event_queue.pop().unwrap_or_else(|| {
-            let ch: i32 = ncurses::getch();
+        let timeout = self.timeout;
+        self.poll_event_within(timeout)
+    }
 
-            // Is it a UTF-8 starting point?
-            if 32 <= ch && ch <= 255 && ch != 127 {
-                utf8::read_char(ch as u8, || Some(ncurses::getch() as u8))
-                    .map(Event::Char)
-                    .unwrap_or_else(|e| {
-                        warn!("Error reading input: {}", e);
-                        Event::Unknown(vec![ch as u8])
-                    })
-            } else {
-                self.parse_ncurses_char(ch)
-            }
-        })
+    fn poll_event_timeout(&mut self, timeout: Duration) -> Event {
+        let timeout = shortest_timeout(self.timeout, timeout);
+        self.poll_event_within(timeout)
+    }
+
+    fn waker(&self) -> Option<Arc<backend::Waker>> {
+        Some(Arc::clone(&self.waker) as Arc<backend::Waker>)
     }
 
     fn set_refresh_rate(&mut self, fps: u32) {
         if fps == 0 {
-            ncurses::timeout(-1);
+            self.timeout = -1;
         } else {
-            ncurses::timeout(1000 / fps as i32);
+            self.timeout = 1000 / fps as i32;
+        }
+    }
+}
+
+impl Drop for Concrete {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.wake_fd);
         }
     }
 }
@@ -376,12 +733,43 @@ where
     }
 }
 
-fn add_fn<F>(start: i32, with_key: F, map: &mut HashMap<i32, Event>)
-where
-    F: Fn(Key) -> Event,
-{
+fn add_fn(start: i32, modifiers: Modifiers, map: &mut HashMap<i32, Event>) {
     for i in 0..12 {
-        map.insert(start + i, with_key(Key::from_f((i + 1) as u8)));
+        let key = Key::from_f((i + 1) as u8);
+        map.insert(start + i, Event::key_with(modifiers, key));
+    }
+}
+
+/// Queries ncurses and the locale for the terminal capabilities.
+///
+/// This must be called after `start_color`.
+fn detect_capabilities() -> backend::Capabilities {
+    let attributes = ncurses::termattrs() as ncurses::attr_t;
+    let mut effects = EnumSet::only(Effect::Simple);
+    for &(effect, attribute) in &[
+        (Effect::Reverse, ncurses::A_REVERSE()),
+        (Effect::Bold, ncurses::A_BOLD()),
+        (Effect::Italic, ncurses::A_ITALIC()),
+        (Effect::Underline, ncurses::A_UNDERLINE()),
+    ] {
+        if attributes & attribute != 0 {
+            effects.insert(effect);
+        }
+    }
+
+    // ncurses only prints unicode characters with an UTF-8 locale.
+    let codeset = unsafe {
+        CStr::from_ptr(libc::nl_langinfo(libc::CODESET))
+            .to_string_lossy()
+            .to_lowercase()
+    };
+
+    backend::Capabilities {
+        color_depth: ColorDepth::from_colors(ncurses::COLORS().max(0) as u32),
+        mouse: ncurses::has_mouse(),
+        effects,
+        box_drawing: codeset == "utf-8" || codeset == "utf8",
+        cursor: true,
     }
 }
 
@@ -412,43 +800,46 @@ fn initialize_keymap() -> HashMap<i32, Event> {
         ncurses::KEY_B2 => Event::Key(Key::NumpadCenter),
         ncurses::KEY_DC => Event::Key(Key::Del),
         ncurses::KEY_IC => Event::Key(Key::Ins),
-        ncurses::KEY_BTAB => Event::Shift(Key::Tab),
-        ncurses::KEY_SLEFT => Event::Shift(Key::Left),
-        ncurses::KEY_SRIGHT => Event::Shift(Key::Right),
+        ncurses::KEY_BTAB => Event::ModKey(Modifiers::SHIFT, Key::Tab),
+        ncurses::KEY_SLEFT => Event::ModKey(Modifiers::SHIFT, Key::Left),
+        ncurses::KEY_SRIGHT => Event::ModKey(Modifiers::SHIFT, Key::Right),
         ncurses::KEY_LEFT => Event::Key(Key::Left),
         ncurses::KEY_RIGHT => Event::Key(Key::Right),
         ncurses::KEY_UP => Event::Key(Key::Up),
         ncurses::KEY_DOWN => Event::Key(Key::Down),
-        ncurses::KEY_SR => Event::Shift(Key::Up),
-        ncurses::KEY_SF => Event::Shift(Key::Down),
+        ncurses::KEY_SR => Event::ModKey(Modifiers::SHIFT, Key::Up),
+        ncurses::KEY_SF => Event::ModKey(Modifiers::SHIFT, Key::Down),
         ncurses::KEY_PPAGE => Event::Key(Key::PageUp),
         ncurses::KEY_NPAGE => Event::Key(Key::PageDown),
         ncurses::KEY_HOME => Event::Key(Key::Home),
         ncurses::KEY_END => Event::Key(Key::End),
-        ncurses::KEY_SHOME => Event::Shift(Key::Home),
-        ncurses::KEY_SEND => Event::Shift(Key::End),
-        ncurses::KEY_SDC => Event::Shift(Key::Del),
-        ncurses::KEY_SNEXT => Event::Shift(Key::PageDown),
-        ncurses::KEY_SPREVIOUS => Event::Shift(Key::PageUp),
+        ncurses::KEY_SHOME => Event::ModKey(Modifiers::SHIFT, Key::Home),
+        ncurses::KEY_SEND => Event::ModKey(Modifiers::SHIFT, Key::End),
+        ncurses::KEY_SDC => Event::ModKey(Modifiers::SHIFT, Key::Del),
+        ncurses::KEY_SNEXT => Event::ModKey(Modifiers::SHIFT, Key::PageDown),
+        ncurses::KEY_SPREVIOUS => Event::ModKey(Modifiers::SHIFT, Key::PageUp),
     };
 
     // Then add some dynamic ones
 
-    for c in 1..26 {
+    for c in 1..=26 {
         let event = match c {
             9 => Event::Key(Key::Tab),
             10 => Event::Key(Key::Enter),
-            other => Event::CtrlChar((b'a' - 1 + other as u8) as char),
+            other => Event::ModChar(
+                Modifiers::CTRL,
+                (b'a' - 1 + other as u8) as char,
+            ),
         };
         map.insert(c, event);
     }
 
     // Ncurses provides a F1 variable, but no modifiers
-    add_fn(ncurses::KEY_F1, Event::Key, &mut map);
-    add_fn(277, Event::Shift, &mut map);
-    add_fn(289, Event::Ctrl, &mut map);
-    add_fn(301, Event::CtrlShift, &mut map);
-    add_fn(313, Event::Alt, &mut map);
+    add_fn(ncurses::KEY_F1, Modifiers::NONE, &mut map);
+    add_fn(277, Modifiers::SHIFT, &mut map);
+    add_fn(289, Modifiers::CTRL, &mut map);
+    add_fn(301, Modifiers::CTRL_SHIFT, &mut map);
+    add_fn(313, Modifiers::ALT, &mut map);
 
     // Those codes actually vary between ncurses versions...
     super::fill_key_codes(&mut map, ncurses::keyname);
@@ -1,13 +1,20 @@
+//! Backend using the pancurses library.
+
+#[cfg(unix)]
+extern crate libc;
 extern crate pancurses;
 
 use self::pancurses::mmask_t;
-use self::super::{find_closest, split_i32};
+#[cfg(unix)]
+use self::super::{disable_suspend_key, new_screen};
+use self::super::{find_closest, shortest_timeout, split_i32};
 use backend;
-use event::{Event, Key, MouseButton, MouseEvent};
+use event::{Event, Key, Modifiers, MouseButton, MouseEvent};
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{stdout, Write};
-use theme::{Color, ColorPair, Effect};
+use std::time::Duration;
+use theme::{Color, ColorDepth, ColorPair, Effect};
 use vec::Vec2;
 
 pub struct Concrete {
@@ -18,10 +25,18 @@ pub struct Concrete {
     key_codes: HashMap<i32, Event>,
 
     last_mouse_button: Option<MouseButton>,
-    event_queue: Vec<Event>,
+    // Events already parsed, waiting to be returned.
+    event_queue: VecDeque<Event>,
 
     // pancurses needs a handle to the current window.
     window: pancurses::Window,
+
+    // Input timeout in milliseconds, -1 to wait forever.
+    timeout: i32,
+
+    capabilities: backend::Capabilities,
+    // Cursor shape last sent to the terminal, if any.
+    cursor_shape: Cell<Option<backend::CursorShape>>,
 }
 
 impl Concrete {
@@ -51,6 +66,43 @@ impl Concrete {
         target
     }
 
+    /// Parses the key following an escape character.
+    ///
+    /// Returns `Key::Esc` if nothing follows.
+    fn parse_alt(&mut self) -> Event {
+        self.window.timeout(0);
+        let input = self.window.getch();
+        self.window.timeout(self.timeout);
+
+        match input {
+            None => Event::Key(Key::Esc),
+            Some(pancurses::Input::Character('\t')) => {
+                Event::ModKey(Modifiers::ALT, Key::Tab)
+            }
+            Some(pancurses::Input::Character('\n')) => {
+                Event::ModKey(Modifiers::ALT, Key::Enter)
+            }
+            Some(pancurses::Input::Character('\u{7f}')) => {
+                Event::ModKey(Modifiers::ALT, Key::Backspace)
+            }
+            Some(pancurses::Input::Character(c)) if (c as u32) <= 26 => {
+                let c = (b'a' - 1 + c as u8) as char;
+                Event::ModChar(Modifiers::CTRL_ALT, c)
+            }
+            // `[` is probably an escape sequence we didn't recognize.
+            Some(pancurses::Input::Character(c))
+                if c != '[' && c != '\u{1b}' =>
+            {
+                Event::ModChar(Modifiers::ALT, c)
+            }
+            Some(input) => {
+                // Keep this key for the next call.
+                self.window.ungetch(&input);
+                Event::Key(Key::Esc)
+            }
+        }
+    }
+
     /// Checks the pair in the cache, or re-define a color if needed.
     fn get_or_create(&self, pair: ColorPair) -> i32 {
         let mut pairs = self.pairs.borrow_mut();
@@ -92,15 +144,11 @@ impl Concrete {
         };
 
         if mevent.bstate == pancurses::REPORT_MOUSE_POSITION as mmask_t {
-            // The event is either a mouse drag event,
-            // or a weird double-release event. :S
-            self.last_mouse_button
-                .map(MouseEvent::Hold)
-                .map(&make_event)
-                .unwrap_or_else(|| {
-                    debug!("We got a mouse drag, but no last mouse pressed?");
-                    Event::Unknown(vec![])
-                })
+            // The mouse moved: this is a drag if a button is held.
+            make_event(
+                self.last_mouse_button
+                    .map_or(MouseEvent::Move, MouseEvent::Hold),
+            )
         } else {
             // Identify the button
             let mut bare_event = mevent.bstate & ((1 << 25) - 1);
@@ -115,13 +163,17 @@ impl Concrete {
                     if event.is_none() {
                         event = Some(e);
                     } else {
-                        self.event_queue.push(make_event(e));
+                        self.event_queue.push_back(make_event(e));
                     }
                 });
             }
             if let Some(event) = event {
-                if let Some(btn) = event.button() {
-                    self.last_mouse_button = Some(btn);
+                match event {
+                    MouseEvent::Press(btn) => {
+                        self.last_mouse_button = Some(btn)
+                    }
+                    MouseEvent::Release(_) => self.last_mouse_button = None,
+                    _ => (),
                 }
                 make_event(event)
             } else {
@@ -133,13 +185,18 @@ impl Concrete {
 }
 
 impl backend::Backend for Concrete {
-    fn init() -> Box<Self> {
+    fn init() -> Result<Box<Self>, backend::Error> {
         ::std::env::set_var("ESCDELAY", "25");
 
-        let window = pancurses::initscr();
+        let window = init_screen()?;
         window.keypad(true);
         pancurses::noecho();
         pancurses::cbreak();
+        #[cfg(unix)]
+        {
+            disable_suspend_key();
+            pancurses::def_prog_mode();
+        }
         pancurses::start_color();
         pancurses::use_default_colors();
         pancurses::curs_set(0);
@@ -149,22 +206,21 @@ impl backend::Backend for Concrete {
             ::std::ptr::null_mut(),
         );
 
-        // This asks the terminal to provide us with mouse drag events
-        // (Mouse move when a button is pressed).
-        // Replacing 1002 with 1003 would give us ANY mouse move.
-        print!("\x1B[?1002h");
-        stdout().flush().expect("could not flush stdout");
+        set_mouse_tracking(true);
 
         let c = Concrete {
             current_style: Cell::new(ColorPair::from_256colors(0, 0)),
             pairs: RefCell::new(HashMap::new()),
             window: window,
             last_mouse_button: None,
-            event_queue: Vec::new(),
+            event_queue: VecDeque::new(),
+            timeout: -1,
             key_codes: initialize_keymap(),
+            capabilities: detect_capabilities(),
+            cursor_shape: Cell::new(None),
         };
 
-        Box::new(c)
+        Ok(Box::new(c))
     }
 
     fn screen_size(&self) -> (usize, usize) {
@@ -176,12 +232,69 @@ impl backend::Backend for Concrete {
         pancurses::has_colors()
     }
 
+    fn color_depth(&self) -> ColorDepth {
+        self.capabilities.color_depth
+    }
+
+    fn capabilities(&self) -> backend::Capabilities {
+        self.capabilities.clone()
+    }
+
     fn finish(&mut self) {
-        print!("\x1B[?1002l");
-        stdout().flush().expect("could not flush stdout");
+        self.suspend();
+    }
+
+    fn suspend(&mut self) {
+        if self.cursor_shape.take().is_some() && !cfg!(windows) {
+            print!("{}", backend::CURSOR_SHAPE_RESET);
+        }
+        set_mouse_tracking(false);
         pancurses::endwin();
     }
 
+    fn resume(&mut self) {
+        // Refreshing after `endwin` brings back the curses mode.
+        self.window.refresh();
+        pancurses::curs_set(0);
+        set_mouse_tracking(true);
+    }
+
+    #[cfg(unix)]
+    fn stop_process(&mut self) {
+        // curses has its own SIGTSTP handler, which would take the
+        // terminal back by itself. We already did what it would do.
+        unsafe {
+            let handler = libc::signal(libc::SIGTSTP, libc::SIG_DFL);
+            libc::raise(libc::SIGTSTP);
+            libc::signal(libc::SIGTSTP, handler);
+        }
+    }
+
+    fn set_title(&mut self, title: &str) {
+        if cfg!(windows) {
+            pancurses::set_title(title);
+        } else {
+            print!("{}", backend::xterm_title(title));
+            stdout().flush().expect("could not flush stdout");
+        }
+    }
+
+    fn bell(&mut self) {
+        pancurses::beep();
+    }
+
+    fn flash(&mut self) {
+        pancurses::flash();
+    }
+
+    fn set_clipboard(&mut self, text: &str) {
+        // The Windows console doesn't understand OSC 52.
+        if !cfg!(windows) {
+            print!("{}", backend::osc52_copy(text));
+            stdout().flush().expect("could not flush stdout");
+        }
+    }
+
     fn set_color(&self, colors: ColorPair) -> ColorPair {
         let current = self.current_style.get();
 
@@ -231,8 +344,26 @@ impl backend::Backend for Concrete {
         self.window.mvaddstr(y as i32, x as i32, text);
     }
 
+    fn show_cursor(
+        &self, (x, y): (usize, usize), shape: backend::CursorShape
+    ) {
+        // On Windows, pdcurses only knows about visibility.
+        if self.cursor_shape.get() != Some(shape) && !cfg!(windows) {
+            print!("{}", shape.xterm_sequence());
+            stdout().flush().expect("could not flush stdout");
+            self.cursor_shape.set(Some(shape));
+        }
+        // `refresh` leaves the cursor where the last move left it.
+        self.window.mv(y as i32, x as i32);
+        pancurses::curs_set(1);
+    }
+
+    fn hide_cursor(&self) {
+        pancurses::curs_set(0);
+    }
+
     fn poll_event(&mut self) -> Event {
-        self.event_queue.pop().unwrap_or_else(|| {
+        self.event_queue.pop_front().unwrap_or_else(|| {
             if let Some(ev) = self.window.getch() {
                 match ev {
                     pancurses::Input::Character('\n') => {
@@ -247,11 +378,10 @@ impl backend::Backend for Concrete {
                     pancurses::Input::Character('\u{9}') => {
                         Event::Key(Key::Tab)
                     }
-                    pancurses::Input::Character('\u{1b}') => {
-                        Event::Key(Key::Esc)
-                    }
+                    pancurses::Input::Character('\u{1b}') => self.parse_alt(),
                     pancurses::Input::Character(c) if (c as u32) <= 26 => {
-                        Event::CtrlChar((b'a' - 1 + c as u8) as char)
+                        let c = (b'a' - 1 + c as u8) as char;
+                        Event::ModChar(Modifiers::CTRL, c)
                     }
                     pancurses::Input::Character(c) => Event::Char(c),
                     // TODO: Some key combos are not recognized by pancurses,
@@ -288,9 +418,15 @@ impl backend::Backend for Concrete {
                     pancurses::Input::KeyF10 => Event::Key(Key::F10),
                     pancurses::Input::KeyF11 => Event::Key(Key::F11),
                     pancurses::Input::KeyF12 => Event::Key(Key::F12),
-                    pancurses::Input::KeyF13 => Event::Shift(Key::F1),
-                    pancurses::Input::KeyF14 => Event::Shift(Key::F2),
-                    pancurses::Input::KeyF15 => Event::Shift(Key::F3),
+                    pancurses::Input::KeyF13 => {
+                        Event::ModKey(Modifiers::SHIFT, Key::F1)
+                    }
+                    pancurses::Input::KeyF14 => {
+                        Event::ModKey(Modifiers::SHIFT, Key::F2)
+                    }
+                    pancurses::Input::KeyF15 => {
+                        Event::ModKey(Modifiers::SHIFT, Key::F3)
+                    }
                     pancurses::Input::KeyDL => Event::Refresh,
                     pancurses::Input::KeyIL => Event::Refresh,
                     pancurses::Input::KeyDC => Event::Key(Key::Del),
@@ -299,13 +435,23 @@ impl backend::Backend for Concrete {
                     pancurses::Input::KeyClear => Event::Refresh,
                     pancurses::Input::KeyEOS => Event::Refresh,
                     pancurses::Input::KeyEOL => Event::Refresh,
-                    pancurses::Input::KeySF => Event::Shift(Key::Down),
-                    pancurses::Input::KeySR => Event::Shift(Key::Up),
+                    pancurses::Input::KeySF => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Down)
+                    }
+                    pancurses::Input::KeySR => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Up)
+                    }
                     pancurses::Input::KeyNPage => Event::Key(Key::PageDown),
                     pancurses::Input::KeyPPage => Event::Key(Key::PageUp),
-                    pancurses::Input::KeySTab => Event::Shift(Key::Tab),
-                    pancurses::Input::KeyCTab => Event::Ctrl(Key::Tab),
-                    pancurses::Input::KeyCATab => Event::CtrlAlt(Key::Tab),
+                    pancurses::Input::KeySTab => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Tab)
+                    }
+                    pancurses::Input::KeyCTab => {
+                        Event::ModKey(Modifiers::CTRL, Key::Tab)
+                    }
+                    pancurses::Input::KeyCATab => {
+                        Event::ModKey(Modifiers::CTRL_ALT, Key::Tab)
+                    }
                     pancurses::Input::KeyEnter => Event::Key(Key::Enter),
                     pancurses::Input::KeySReset => Event::Refresh,
                     pancurses::Input::KeyReset => Event::Refresh,
@@ -314,7 +460,9 @@ impl backend::Backend for Concrete {
                     pancurses::Input::KeyAbort => Event::Refresh,
                     pancurses::Input::KeySHelp => Event::Refresh,
                     pancurses::Input::KeyLHelp => Event::Refresh,
-                    pancurses::Input::KeyBTab => Event::Shift(Key::Tab),
+                    pancurses::Input::KeyBTab => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Tab)
+                    }
                     pancurses::Input::KeyBeg => Event::Refresh,
                     pancurses::Input::KeyCancel => Event::Refresh,
                     pancurses::Input::KeyClose => Event::Refresh,
@@ -344,27 +492,41 @@ impl backend::Backend for Concrete {
                     pancurses::Input::KeySCommand => Event::Refresh,
                     pancurses::Input::KeySCopy => Event::Refresh,
                     pancurses::Input::KeySCreate => Event::Refresh,
-                    pancurses::Input::KeySDC => Event::Shift(Key::Del),
+                    pancurses::Input::KeySDC => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Del)
+                    }
                     pancurses::Input::KeySDL => Event::Refresh,
                     pancurses::Input::KeySelect => Event::Refresh,
-                    pancurses::Input::KeySEnd => Event::Shift(Key::End),
+                    pancurses::Input::KeySEnd => {
+                        Event::ModKey(Modifiers::SHIFT, Key::End)
+                    }
                     pancurses::Input::KeySEOL => Event::Refresh,
                     pancurses::Input::KeySExit => Event::Refresh,
                     pancurses::Input::KeySFind => Event::Refresh,
-                    pancurses::Input::KeySHome => Event::Shift(Key::Home),
-                    pancurses::Input::KeySIC => Event::Shift(Key::Ins),
-                    pancurses::Input::KeySLeft => Event::Shift(Key::Left),
+                    pancurses::Input::KeySHome => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Home)
+                    }
+                    pancurses::Input::KeySIC => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Ins)
+                    }
+                    pancurses::Input::KeySLeft => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Left)
+                    }
                     pancurses::Input::KeySMessage => Event::Refresh,
                     pancurses::Input::KeySMove => Event::Refresh,
-                    pancurses::Input::KeySNext => Event::Shift(Key::PageDown),
+                    pancurses::Input::KeySNext => {
+                        Event::ModKey(Modifiers::SHIFT, Key::PageDown)
+                    }
                     pancurses::Input::KeySOptions => Event::Refresh,
                     pancurses::Input::KeySPrevious => {
-                        Event::Shift(Key::PageUp)
+                        Event::ModKey(Modifiers::SHIFT, Key::PageUp)
                     }
                     pancurses::Input::KeySPrint => Event::Refresh,
                     pancurses::Input::KeySRedo => Event::Refresh,
                     pancurses::Input::KeySReplace => Event::Refresh,
-                    pancurses::Input::KeySRight => Event::Shift(Key::Right),
+                    pancurses::Input::KeySRight => {
+                        Event::ModKey(Modifiers::SHIFT, Key::Right)
+                    }
                     pancurses::Input::KeySResume => Event::Refresh,
                     pancurses::Input::KeySSave => Event::Refresh,
                     pancurses::Input::KeySSuspend => Event::Refresh,
@@ -382,7 +544,6 @@ impl backend::Backend for Concrete {
                         Event::WindowResize
                     }
                     pancurses::Input::KeyEvent => Event::Refresh,
-                    // TODO: mouse support
                     pancurses::Input::KeyMouse => self.parse_mouse_event(),
                     pancurses::Input::KeyA1 => Event::Refresh,
                     pancurses::Input::KeyA3 => Event::Refresh,
@@ -396,13 +557,82 @@ impl backend::Backend for Concrete {
         })
     }
 
+    fn poll_event_timeout(&mut self, timeout: Duration) -> Event {
+        self.window.timeout(shortest_timeout(self.timeout, timeout));
+        let event = self.poll_event();
+        self.window.timeout(self.timeout);
+        event
+    }
+
     fn set_refresh_rate(&mut self, fps: u32) {
         if fps == 0 {
-            self.window.timeout(-1);
+            self.timeout = -1;
         } else {
-            self.window.timeout(1000 / fps as i32);
+            self.timeout = 1000 / fps as i32;
+        }
+        self.window.timeout(self.timeout);
+    }
+}
+
+/// Starts curses on stdin and stdout.
+#[cfg(unix)]
+fn init_screen() -> Result<pancurses::Window, backend::Error> {
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) == 0
+            || libc::isatty(libc::STDOUT_FILENO) == 0
+        {
+            return Err(backend::Error::NotATty);
         }
+
+        // This is what `initscr` does first, to get UTF-8 support.
+        libc::setlocale(libc::LC_ALL, b"\0".as_ptr() as _);
+    }
+
+    // The streams are never closed, since they wrap stdin and stdout.
+    new_screen(::std::env::var("TERM").ok(), |term| unsafe {
+        let output = libc::fdopen(libc::STDOUT_FILENO, b"w\0".as_ptr() as _);
+        let input = libc::fdopen(libc::STDIN_FILENO, b"r\0".as_ptr() as _);
+        pancurses::newterm(Some(term), output as _, input as _)
+    })?;
+
+    // pancurses doesn't give access to `stdscr` without `initscr`.
+    // A window covering the whole screen works the same, and is resized
+    // along with the terminal.
+    Ok(pancurses::newwin(0, 0, 0, 0))
+}
+
+/// Starts curses on the console.
+#[cfg(not(unix))]
+fn init_screen() -> Result<pancurses::Window, backend::Error> {
+    Ok(pancurses::initscr())
+}
+
+/// Queries pancurses and the locale for the terminal capabilities.
+///
+/// This must be called after `start_color`.
+fn detect_capabilities() -> backend::Capabilities {
+    let colors = pancurses::COLORS().max(0) as u32;
+    backend::Capabilities {
+        color_depth: ColorDepth::from_colors(colors),
+        box_drawing: backend::utf8_locale().unwrap_or(false),
+        cursor: true,
+        ..backend::Capabilities::default()
+    }
+}
+
+/// Asks the terminal to send mouse move events, or to stop sending them.
+///
+/// Every move is sent, with or without a button pressed. They are reported
+/// by pancurses as `REPORT_MOUSE_POSITION`.
+///
+/// On Windows, pdcurses tracks the mouse by itself, and there is no
+/// terminal to send escape sequences to.
+fn set_mouse_tracking(enabled: bool) {
+    if cfg!(windows) {
+        return;
     }
+    print!("\x1B[?1003{}", if enabled { 'h' } else { 'l' });
+    stdout().flush().expect("could not flush stdout");
 }
 
 /// Parse the given code into one or more event.
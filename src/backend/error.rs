@@ -0,0 +1,61 @@
+use std::error;
+use std::fmt;
+use std::io;
+use vec::Vec2;
+
+/// Error returned when a backend cannot be initialized.
+///
+/// When this happens, the terminal is left untouched, so the application
+/// can still fall back to plain text output.
+#[derive(Debug)]
+pub enum Error {
+    /// The standard input or output is not a terminal.
+    ///
+    /// This happens when running in a pipe, or in most CI environments.
+    NotATty,
+    /// The terminal type, from the `TERM` environment variable,
+    /// is not supported.
+    UnknownTerminal(String),
+    /// The terminal is too small to show anything.
+    TooSmall(Vec2),
+    /// An error occured while setting up the terminal.
+    Io(io::Error),
+    /// No backend with this name is enabled.
+    UnknownBackend(String),
+    /// No backend is enabled at all.
+    NoBackend,
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NotATty => write!(f, "not running in a terminal"),
+            Error::UnknownTerminal(ref term) => {
+                write!(f, "unknown terminal type: {:?}", term)
+            }
+            Error::TooSmall(size) => {
+                write!(f, "terminal is too small: {}x{}", size.x, size.y)
+            }
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::UnknownBackend(ref name) => {
+                write!(f, "unknown backend: {:?}", name)
+            }
+            Error::NoBackend => write!(f, "no backend is enabled"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -1,22 +1,260 @@
-use event;
+use event::{self, Modifiers};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
 use theme;
 
 #[cfg(feature = "termion")]
-mod termion;
+pub mod termion;
 #[cfg(feature = "bear-lib-terminal")]
-mod blt;
+pub mod blt;
 #[cfg(any(feature = "ncurses", feature = "pancurses"))]
-mod curses;
+pub mod curses;
 
-#[cfg(feature = "bear-lib-terminal")]
-pub use self::blt::*;
-#[cfg(any(feature = "ncurses", feature = "pancurses"))]
-pub use self::curses::*;
+mod buffer;
+mod capabilities;
+mod error;
+pub mod puppet;
 #[cfg(feature = "termion")]
-pub use self::termion::*;
+mod terminfo;
+
+pub use self::buffer::Buffered;
+pub use self::capabilities::Capabilities;
+#[cfg(any(feature = "termion", feature = "pancurses"))]
+use self::capabilities::utf8_locale;
+pub use self::error::Error;
+
+// `Concrete` is the preferred backend among the enabled ones.
+#[cfg(feature = "ncurses")]
+pub use self::curses::n::Concrete;
+#[cfg(all(feature = "pancurses", not(feature = "ncurses")))]
+pub use self::curses::pan::Concrete;
+#[cfg(
+    all(
+        feature = "termion",
+        not(any(feature = "ncurses", feature = "pancurses"))
+    )
+)]
+pub use self::termion::Concrete;
+#[cfg(
+    all(
+        feature = "bear-lib-terminal",
+        not(any(
+            feature = "ncurses",
+            feature = "pancurses",
+            feature = "termion"
+        ))
+    )
+)]
+pub use self::blt::Concrete;
+
+/// Environment variable used to pick a backend at runtime.
+///
+/// See [`init_default`](fn.init_default.html).
+pub const BACKEND_VAR: &str = "CURSIVE_BACKEND";
+
+/// Function initializing a backend.
+type Init = fn() -> Result<Box<Backend>, Error>;
+
+fn init_boxed<B: Backend + 'static>() -> Result<Box<Backend>, Error> {
+    B::init().map(|backend| backend as Box<Backend>)
+}
+
+/// Returns every enabled backend, in order of preference.
+fn registry() -> Vec<(&'static str, Init)> {
+    let mut backends: Vec<(&'static str, Init)> = Vec::new();
+
+    #[cfg(feature = "ncurses")]
+    backends.push(("ncurses", init_boxed::<curses::n::Concrete>));
+    #[cfg(feature = "pancurses")]
+    backends.push(("pancurses", init_boxed::<curses::pan::Concrete>));
+    #[cfg(feature = "termion")]
+    backends.push(("termion", init_boxed::<termion::Concrete>));
+    #[cfg(feature = "bear-lib-terminal")]
+    backends.push(("blt", init_boxed::<blt::Concrete>));
+
+    backends
+}
+
+/// Returns the names of the enabled backends, in order of preference.
+///
+/// Several backends can be enabled at once with cargo features,
+/// and picked at runtime with [`init_by_name`].
+///
+/// [`init_by_name`]: fn.init_by_name.html
+pub fn names() -> Vec<&'static str> {
+    registry().into_iter().map(|(name, _)| name).collect()
+}
+
+/// Initializes the backend with the given name.
+///
+/// Returns `Error::UnknownBackend` if no such backend is enabled.
+pub fn init_by_name(name: &str) -> Result<Box<Backend>, Error> {
+    match registry().into_iter().find(|&(n, _)| n == name) {
+        Some((_, init)) => init(),
+        None => Err(Error::UnknownBackend(name.to_string())),
+    }
+}
+
+/// Initializes the best available backend.
+///
+/// If the `CURSIVE_BACKEND` environment variable is set, the backend with
+/// this name is used. Otherwise, each enabled backend is tried in order of
+/// preference, until one succeeds. In that case, the error from the first
+/// backend is returned if all of them fail.
+pub fn init_default() -> Result<Box<Backend>, Error> {
+    if let Ok(name) = env::var(BACKEND_VAR) {
+        if !name.is_empty() {
+            return init_by_name(&name);
+        }
+    }
+
+    let mut first_error = None;
+    for (_, init) in registry() {
+        match init() {
+            Ok(backend) => return Ok(backend),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    Err(first_error.unwrap_or(Error::NoBackend))
+}
+
+/// Wakes up a backend waiting for input.
+///
+/// This is used from other threads, to interrupt a blocking `poll_event`.
+pub trait Waker: Send + Sync {
+    /// Makes the pending (or next) call to `poll_event` return.
+    ///
+    /// The interrupted `poll_event` returns `Event::Refresh`.
+    fn wake(&self);
+}
+
+/// Shape of the terminal cursor.
+///
+/// See [`Printer::set_cursor`](../struct.Printer.html#method.set_cursor).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    /// The whole cell.
+    Block,
+    /// A line under the cell.
+    Underline,
+    /// A vertical line on the left of the cell.
+    Bar,
+}
+
+impl CursorShape {
+    /// Returns the xterm sequence selecting this shape (`DECSCUSR`).
+    fn xterm_sequence(self) -> &'static str {
+        match self {
+            CursorShape::Block => "\x1B[2 q",
+            CursorShape::Underline => "\x1B[4 q",
+            CursorShape::Bar => "\x1B[6 q",
+        }
+    }
+}
+
+/// Restores the default cursor shape of the terminal.
+const CURSOR_SHAPE_RESET: &str = "\x1B[0 q";
+
+/// Returns the xterm sequence setting the window title to `title`.
+///
+/// Control characters are removed, so they cannot end the sequence early.
+fn xterm_title(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1B]2;{}\x07", title)
+}
+
+/// Returns the OSC 52 sequence copying `text` to the clipboard.
+fn osc52_copy(text: &str) -> String {
+    format!("\x1B]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Encodes `data` in standard base64, with padding.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::new();
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8)
+            | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3F;
+                result.push(ALPHABET[index as usize] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+/// Sent by the terminal before pasted text, in bracketed paste mode.
+const PASTE_START: &[u8] = b"\x1B[200~";
+
+/// Sent by the terminal after pasted text, in bracketed paste mode.
+const PASTE_END: &[u8] = b"\x1B[201~";
+
+/// Sent by the terminal when it gains focus, in focus reporting mode.
+const FOCUS_IN: &[u8] = b"\x1B[I";
+
+/// Sent by the terminal when it loses focus, in focus reporting mode.
+const FOCUS_OUT: &[u8] = b"\x1B[O";
+
+/// How long to wait for the rest of a paste, in milliseconds.
+///
+/// We don't want to hang if the end of a paste never comes.
+const PASTE_TIMEOUT: u32 = 100;
+
+/// Converts a timeout to milliseconds, rounding up.
+///
+/// This way, we never wake up before a deadline.
+fn timeout_ms(timeout: Duration) -> u64 {
+    let millis = timeout.as_secs() * 1000;
+    let nanos = u64::from(timeout.subsec_nanos());
+    millis + (nanos + 999_999) / 1_000_000
+}
+
+/// Decodes the modifier parameter used by xterm in key sequences.
+///
+/// Returns `None` if there is no modifier.
+fn xterm_modifiers(code: u8) -> Option<Modifiers> {
+    if code < 2 || code > 16 {
+        return None;
+    }
+    // The parameter is 1 + a bitmask of the modifiers.
+    let bits = code - 1;
+    let mut modifiers = Modifiers::NONE;
+    if bits & 1 != 0 {
+        modifiers |= Modifiers::SHIFT;
+    }
+    if bits & 2 != 0 {
+        modifiers |= Modifiers::ALT;
+    }
+    if bits & 4 != 0 {
+        modifiers |= Modifiers::CTRL;
+    }
+    if bits & 8 != 0 {
+        modifiers |= Modifiers::SUPER;
+    }
+    Some(modifiers)
+}
 
 pub trait Backend {
-    fn init() -> Box<Self> where Self: Sized;
+    /// Takes control of the terminal.
+    ///
+    /// On error, the terminal should be left as it was.
+    fn init() -> Result<Box<Self>, Error> where Self: Sized;
     // TODO: take `self` by value?
     // Or implement Drop?
     fn finish(&mut self);
@@ -24,13 +262,119 @@ pub trait Backend {
     fn refresh(&mut self);
 
     fn has_colors(&self) -> bool;
+
+    /// Returns the colors this backend can show.
+    ///
+    /// Colors are downgraded to this depth before being sent to the backend.
+    ///
+    /// Default implementation returns `ColorDepth::TrueColor`.
+    fn color_depth(&self) -> theme::ColorDepth {
+        theme::ColorDepth::TrueColor
+    }
+
+    /// Returns the features supported by this backend.
+    ///
+    /// Default implementation uses `color_depth`, and reports everything
+    /// else as supported, except the cursor: the default `show_cursor`
+    /// does nothing, so views draw their own.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            color_depth: self.color_depth(),
+            cursor: false,
+            ..Capabilities::default()
+        }
+    }
     fn screen_size(&self) -> (usize, usize);
 
     /// Main input method
     fn poll_event(&mut self) -> event::Event;
 
+    /// Waits for an event, but not longer than `timeout`.
+    ///
+    /// Returns `Event::Refresh` if nothing happened in time.
+    ///
+    /// Default implementation ignores the timeout and calls `poll_event`.
+    fn poll_event_timeout(&mut self, timeout: Duration) -> event::Event {
+        let _ = timeout;
+        self.poll_event()
+    }
+
+    /// Returns a handle to interrupt `poll_event` from another thread.
+    ///
+    /// Backends that cannot be interrupted return `None`.
+    /// Default implementation returns `None`.
+    fn waker(&self) -> Option<Arc<Waker>> {
+        None
+    }
+
+    /// Gives the terminal back, for instance to run another program.
+    ///
+    /// The terminal should be left as it was before `init`, and no input
+    /// should be read until `resume` is called.
+    ///
+    /// Default implementation does nothing.
+    fn suspend(&mut self) {}
+
+    /// Takes the terminal back after a call to `suspend`.
+    ///
+    /// The whole screen will be redrawn after this.
+    ///
+    /// Default implementation does nothing.
+    fn resume(&mut self) {}
+
+    /// Stops the process, like Ctrl-Z in a shell.
+    ///
+    /// This is only called while suspended. It returns once the process
+    /// is continued (for instance with `fg`).
+    ///
+    /// Default implementation does nothing.
+    fn stop_process(&mut self) {}
+
+    /// Sets the title of the terminal window.
+    ///
+    /// Default implementation does nothing.
+    fn set_title(&mut self, title: &str) {
+        let _ = title;
+    }
+
+    /// Rings the terminal bell.
+    ///
+    /// Default implementation does nothing.
+    fn bell(&mut self) {}
+
+    /// Briefly flashes the screen, as a visual bell.
+    ///
+    /// Default implementation rings the bell instead.
+    fn flash(&mut self) {
+        self.bell();
+    }
+
+    /// Copies `text` to the system clipboard.
+    ///
+    /// Default implementation does nothing.
+    fn set_clipboard(&mut self, text: &str) {
+        let _ = text;
+    }
+
     /// Main method used for printing
     fn print_at(&self, (usize, usize), &str);
+
+    /// Shows the cursor at the given position, with the given shape.
+    ///
+    /// This is called after everything is printed, right before
+    /// `refresh`. The cursor should otherwise stay hidden.
+    ///
+    /// Default implementation does nothing. Backends implementing it
+    /// should report `cursor` in their `capabilities`.
+    fn show_cursor(&self, position: (usize, usize), shape: CursorShape) {
+        let _ = (position, shape);
+    }
+
+    /// Hides the cursor.
+    ///
+    /// Default implementation does nothing.
+    fn hide_cursor(&self) {}
+
     fn clear(&self, color: theme::Color);
 
     fn set_refresh_rate(&mut self, fps: u32);
@@ -42,3 +386,65 @@ pub trait Backend {
     fn set_effect(&self, effect: theme::Effect);
     fn unset_effect(&self, effect: theme::Effect);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_sequences() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("héllo".as_bytes()), "aMOpbGxv");
+
+        assert_eq!(osc52_copy("foo"), "\x1B]52;c;Zm9v\x07");
+        assert_eq!(xterm_title("a\x07b\x1B"), "\x1B]2;ab\x07");
+    }
+
+    // Only implements the required methods.
+    struct Minimal;
+
+    impl Backend for Minimal {
+        fn init() -> Result<Box<Self>, Error> {
+            Ok(Box::new(Minimal))
+        }
+        fn finish(&mut self) {}
+        fn refresh(&mut self) {}
+        fn has_colors(&self) -> bool {
+            true
+        }
+        fn screen_size(&self) -> (usize, usize) {
+            (80, 24)
+        }
+        fn poll_event(&mut self) -> event::Event {
+            event::Event::Refresh
+        }
+        fn print_at(&self, _: (usize, usize), _: &str) {}
+        fn clear(&self, _: theme::Color) {}
+        fn set_refresh_rate(&mut self, _: u32) {}
+        fn set_color(&self, colors: theme::ColorPair) -> theme::ColorPair {
+            colors
+        }
+        fn set_effect(&self, _: theme::Effect) {}
+        fn unset_effect(&self, _: theme::Effect) {}
+    }
+
+    #[test]
+    fn default_capabilities() {
+        // Without `show_cursor`, views must draw the cursor themselves.
+        let capabilities = Minimal.capabilities();
+        assert!(!capabilities.cursor);
+        assert!(capabilities.box_drawing);
+    }
+
+    #[test]
+    fn unknown_backend() {
+        assert!(!names().contains(&"puppet"));
+        match init_by_name("puppet") {
+            Err(Error::UnknownBackend(name)) => assert_eq!(name, "puppet"),
+            _ => panic!("puppet is not a registered backend"),
+        }
+    }
+}
@@ -0,0 +1,454 @@
+//! Headless backend, driven by a scripted list of events.
+//!
+//! Nothing is ever sent to a terminal: every print ends up in an in-memory
+//! grid of cells, which can later be inspected.
+//!
+//! This is mostly useful to test views from end to end, by running
+//! `Cursive::step` and checking what was drawn.
+
+use backend::{self, CursorShape};
+use enumset::EnumSet;
+use event::Event;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+use theme::{Color, ColorDepth, ColorPair, Effect};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+use vec::Vec2;
+
+/// Size used by `Puppet::init()`.
+const DEFAULT_SIZE: (usize, usize) = (80, 24);
+
+/// A single cell of an `ObservedScreen`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ObservedCell {
+    /// Grapheme printed in this cell.
+    ///
+    /// This is empty when the cell is covered by the previous one
+    /// (for instance with double-width characters).
+    pub letter: String,
+
+    /// Colors used when printing this cell.
+    pub colors: ColorPair,
+
+    /// Effects active when printing this cell.
+    pub effects: EnumSet<Effect>,
+}
+
+impl ObservedCell {
+    fn blank(color: Color) -> Self {
+        ObservedCell {
+            letter: String::from(" "),
+            colors: ColorPair {
+                front: color,
+                back: color,
+            },
+            effects: EnumSet::new(),
+        }
+    }
+
+    /// Returns `true` if this cell is covered by the previous one.
+    pub fn is_continuation(&self) -> bool {
+        self.letter.is_empty()
+    }
+}
+
+/// In-memory copy of everything drawn by a `Puppet` backend.
+#[derive(Clone, Debug)]
+pub struct ObservedScreen {
+    size: Vec2,
+    cells: Vec<ObservedCell>,
+    cursor: Option<(Vec2, CursorShape)>,
+    title: Option<String>,
+    clipboard: Option<String>,
+}
+
+impl ObservedScreen {
+    /// Creates a new blank screen of the given size.
+    pub fn new<S: Into<Vec2>>(size: S) -> Self {
+        let size = size.into();
+        ObservedScreen {
+            size,
+            cells: vec![
+                ObservedCell::blank(Color::TerminalDefault);
+                size.x * size.y
+            ],
+            cursor: None,
+            title: None,
+            clipboard: None,
+        }
+    }
+
+    /// Returns the size of this screen.
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Returns the cell at the given position, if any.
+    pub fn cell<S: Into<Vec2>>(&self, pos: S) -> Option<&ObservedCell> {
+        let pos = pos.into();
+        if pos.x < self.size.x && pos.y < self.size.y {
+            Some(&self.cells[pos.y * self.size.x + pos.x])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cells of the given row.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is outside of the screen.
+    pub fn row(&self, y: usize) -> &[ObservedCell] {
+        let start = y * self.size.x;
+        &self.cells[start..start + self.size.x]
+    }
+
+    /// Returns the text content of the given row.
+    pub fn line(&self, y: usize) -> String {
+        self.row(y).iter().map(|cell| cell.letter.as_str()).collect()
+    }
+
+    /// Returns the position and shape of the cursor, if it is shown.
+    pub fn cursor(&self) -> Option<(Vec2, CursorShape)> {
+        self.cursor
+    }
+
+    /// Returns the last title given to the terminal window, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the last text copied to the clipboard, if any.
+    pub fn clipboard(&self) -> Option<&str> {
+        self.clipboard.as_deref()
+    }
+
+    /// Returns the text content of the whole screen, one line per row.
+    pub fn text(&self) -> String {
+        (0..self.size.y)
+            .map(|y| self.line(y))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn clear(&mut self, color: Color) {
+        for cell in &mut self.cells {
+            *cell = ObservedCell::blank(color);
+        }
+    }
+
+    fn print_at(
+        &mut self, pos: Vec2, text: &str, colors: ColorPair,
+        effects: EnumSet<Effect>,
+    ) {
+        if pos.y >= self.size.y {
+            return;
+        }
+
+        let mut x = pos.x;
+        for grapheme in text.graphemes(true) {
+            let width = grapheme.width();
+            if x + width > self.size.x {
+                break;
+            }
+
+            let offset = pos.y * self.size.x + x;
+            self.cells[offset] = ObservedCell {
+                letter: String::from(grapheme),
+                colors,
+                effects,
+            };
+            for i in 1..width {
+                self.cells[offset + i] = ObservedCell {
+                    letter: String::new(),
+                    colors,
+                    effects,
+                };
+            }
+
+            x += width;
+        }
+    }
+}
+
+impl fmt::Display for ObservedScreen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text())
+    }
+}
+
+/// Backend keeping everything in memory.
+///
+/// Input comes from a queue of events, filled with [`input`].
+/// When this queue is empty, `poll_event` returns `Event::Refresh` instead
+/// of blocking.
+///
+/// # Examples
+///
+/// ```rust
+/// # extern crate cursive;
+/// # use cursive::Cursive;
+/// # use cursive::backend::puppet::Puppet;
+/// # use cursive::views::TextView;
+/// # fn main() {
+/// let backend = Puppet::new((20, 5));
+/// let screen = backend.screen();
+///
+/// let mut siv = Cursive::with_backend(backend);
+/// siv.root_mut().add_fullscreen_layer(TextView::new("Hello"));
+/// siv.step();
+///
+/// assert_eq!(screen.borrow().line(0).trim(), "Hello");
+/// # }
+/// ```
+///
+/// [`input`]: #method.input
+pub struct Puppet {
+    screen: Rc<RefCell<ObservedScreen>>,
+    input: Rc<RefCell<VecDeque<Event>>>,
+
+    current_style: Cell<ColorPair>,
+    current_effects: Cell<EnumSet<Effect>>,
+
+    capabilities: backend::Capabilities,
+}
+
+impl Puppet {
+    /// Creates a new puppet backend with the given screen size.
+    pub fn new<S: Into<Vec2>>(size: S) -> Box<Self> {
+        Box::new(Puppet {
+            screen: Rc::new(RefCell::new(ObservedScreen::new(size))),
+            input: Rc::new(RefCell::new(VecDeque::new())),
+            current_style: Cell::new(ColorPair::from_256colors(0, 0)),
+            current_effects: Cell::new(EnumSet::new()),
+            capabilities: backend::Capabilities::default(),
+        })
+    }
+
+    /// Sets the capabilities reported by this backend.
+    ///
+    /// By default, every feature is supported.
+    pub fn set_capabilities(&mut self, capabilities: backend::Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Returns a handle to the observed screen.
+    ///
+    /// The handle stays valid after the backend is given to `Cursive`.
+    pub fn screen(&self) -> Rc<RefCell<ObservedScreen>> {
+        Rc::clone(&self.screen)
+    }
+
+    /// Returns a handle to the input queue.
+    ///
+    /// Events pushed to the back of this queue will be returned,
+    /// in order, by `poll_event`.
+    pub fn input(&self) -> Rc<RefCell<VecDeque<Event>>> {
+        Rc::clone(&self.input)
+    }
+}
+
+impl backend::Backend for Puppet {
+    fn init() -> Result<Box<Self>, backend::Error> {
+        Ok(Puppet::new(DEFAULT_SIZE))
+    }
+
+    fn finish(&mut self) {}
+
+    fn refresh(&mut self) {}
+
+    fn has_colors(&self) -> bool {
+        true
+    }
+
+    fn color_depth(&self) -> ColorDepth {
+        self.capabilities.color_depth
+    }
+
+    fn capabilities(&self) -> backend::Capabilities {
+        self.capabilities.clone()
+    }
+
+    fn screen_size(&self) -> (usize, usize) {
+        let size = self.screen.borrow().size();
+        (size.x, size.y)
+    }
+
+    fn poll_event(&mut self) -> Event {
+        self.input
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or(Event::Refresh)
+    }
+
+    fn print_at(&self, (x, y): (usize, usize), text: &str) {
+        self.screen.borrow_mut().print_at(
+            Vec2::new(x, y),
+            text,
+            self.current_style.get(),
+            self.current_effects.get(),
+        );
+    }
+
+    fn show_cursor(&self, (x, y): (usize, usize), shape: CursorShape) {
+        self.screen.borrow_mut().cursor = Some((Vec2::new(x, y), shape));
+    }
+
+    fn hide_cursor(&self) {
+        self.screen.borrow_mut().cursor = None;
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.screen.borrow_mut().title = Some(title.to_string());
+    }
+
+    fn set_clipboard(&mut self, text: &str) {
+        self.screen.borrow_mut().clipboard = Some(text.to_string());
+    }
+
+    fn clear(&self, color: Color) {
+        self.screen.borrow_mut().clear(color);
+    }
+
+    fn set_refresh_rate(&mut self, _: u32) {}
+
+    fn set_color(&self, colors: ColorPair) -> ColorPair {
+        self.current_style.replace(colors)
+    }
+
+    fn set_effect(&self, effect: Effect) {
+        if effect == Effect::Simple {
+            return;
+        }
+        let mut effects = self.current_effects.get();
+        effects.insert(effect);
+        self.current_effects.set(effects);
+    }
+
+    fn unset_effect(&self, effect: Effect) {
+        let mut effects = self.current_effects.get();
+        effects.remove(effect);
+        self.current_effects.set(effects);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Cursive;
+    use event::Key;
+    use theme::{BaseColor, ColorStyle, PaletteColor};
+    use view::Boxable;
+    use views::{Button, EditView, LinearLayout, Panel, TextView};
+
+    #[test]
+    fn prints_text() {
+        let backend = Puppet::new((10, 3));
+        let screen = backend.screen();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut()
+            .add_fullscreen_layer(TextView::new("Hello\nworld"));
+        siv.step();
+
+        let screen = screen.borrow();
+        assert_eq!(screen.line(0), "Hello     ");
+        assert_eq!(screen.line(1), "world     ");
+
+        let palette = &siv.current_theme().palette;
+        let expected = ColorStyle::primary().resolve(palette);
+        assert_eq!(screen.cell((0, 0)).unwrap().colors, expected);
+    }
+
+    #[test]
+    fn wide_characters() {
+        let mut screen = ObservedScreen::new((4, 1));
+        let colors = ColorPair::from_256colors(0, 0);
+        screen.print_at(Vec2::zero(), "日本語", colors, EnumSet::new());
+
+        assert_eq!(screen.line(0), "日本");
+        assert!(screen.cell((1, 0)).unwrap().is_continuation());
+    }
+
+    #[test]
+    fn scripted_input() {
+        let backend = Puppet::new((10, 1));
+        let screen = backend.screen();
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut()
+            .add_fullscreen_layer(EditView::new().full_width());
+
+        input.borrow_mut().extend(vec![
+            Event::Char('a'),
+            Event::Char('b'),
+            Event::Key(Key::Backspace),
+            Event::Char('c'),
+        ]);
+        for _ in 0..5 {
+            siv.step();
+        }
+
+        assert!(screen.borrow().line(0).starts_with("ac"));
+    }
+
+    #[test]
+    fn hardware_cursor() {
+        let backend = Puppet::new((10, 2));
+        let screen = backend.screen();
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut().add_fullscreen_layer(
+            LinearLayout::vertical()
+                .child(EditView::new().full_width())
+                .child(Button::new_raw("Ok", |_| ())),
+        );
+
+        input.borrow_mut().extend(vec![Event::Char('a'), Event::Char('b')]);
+        for _ in 0..3 {
+            siv.step();
+        }
+        let cursor = screen.borrow().cursor();
+        assert_eq!(cursor, Some((Vec2::new(2, 0), CursorShape::Block)));
+
+        // Without focus, the cursor goes away.
+        input.borrow_mut().push_back(Event::Key(Key::Down));
+        siv.step();
+        siv.step();
+        assert_eq!(screen.borrow().cursor(), None);
+    }
+
+    #[test]
+    fn limited_capabilities() {
+        let mut backend = Puppet::new((12, 3));
+        backend.set_capabilities(backend::Capabilities {
+            color_depth: ColorDepth::Colors8,
+            effects: EnumSet::only(Effect::Simple),
+            box_drawing: false,
+            ..backend::Capabilities::default()
+        });
+        let screen = backend.screen();
+
+        let mut siv = Cursive::with_backend(backend);
+        let mut theme = siv.current_theme().clone();
+        theme.palette[PaletteColor::View] = Color::Rgb(255, 40, 40);
+        siv.set_theme(theme);
+        siv.root_mut().add_fullscreen_layer(Panel::new(
+            TextView::new("Hi").effect(Effect::Bold),
+        ));
+        siv.step();
+
+        let screen = screen.borrow();
+        assert_eq!(screen.line(0), "+--+        ");
+        assert_eq!(screen.line(1), "|Hi|        ");
+
+        let cell = screen.cell((1, 1)).unwrap();
+        assert!(cell.effects.is_empty());
+        assert_eq!(cell.colors.back, Color::Dark(BaseColor::Red));
+    }
+}
@@ -0,0 +1,167 @@
+//! Minimal reader for compiled terminfo files.
+//!
+//! Only numeric capabilities are read, for backends that don't go through
+//! a curses library.
+
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Magic number of the legacy format, with 16-bit numbers.
+const MAGIC: i16 = 0o432;
+/// Magic number of the extended format, with 32-bit numbers.
+const MAGIC_32: i16 = 0o1036;
+
+/// Index of the `colors` capability among numbers.
+const COLORS: usize = 13;
+
+/// Returns the number of colors supported by the given terminal type.
+///
+/// Returns `None` if no terminfo entry was found, or if it doesn't
+/// specify the number of colors.
+pub fn colors(term: &str) -> Option<u32> {
+    let data = read_entry(term)?;
+    let colors = number(&data, COLORS)?;
+    if colors < 0 {
+        None
+    } else {
+        Some(colors as u32)
+    }
+}
+
+/// Finds and reads the terminfo file for the given terminal type.
+fn read_entry(term: &str) -> Option<Vec<u8>> {
+    let first = term.chars().next()?;
+    if term.contains('/') {
+        return None;
+    }
+
+    for dir in directories() {
+        // Some systems use the hexadecimal code of the first letter.
+        let subdirs = [first.to_string(), format!("{:x}", first as u32)];
+        for subdir in &subdirs {
+            let path = dir.join(subdir).join(term);
+            let mut data = Vec::new();
+            if let Ok(mut file) = File::open(&path) {
+                if file.read_to_end(&mut data).is_ok() {
+                    return Some(data);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the directories where terminfo files are looked for, in order.
+fn directories() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(PathBuf::from(dir));
+    }
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        for dir in list.split(':').filter(|dir| !dir.is_empty()) {
+            dirs.push(PathBuf::from(dir));
+        }
+    }
+    for dir in &[
+        "/etc/terminfo",
+        "/lib/terminfo",
+        "/usr/share/terminfo",
+        "/usr/lib/terminfo",
+    ] {
+        dirs.push(PathBuf::from(dir));
+    }
+
+    dirs
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(i16::from(bytes[0]) | (i16::from(bytes[1]) << 8))
+}
+
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(
+        i32::from(bytes[0])
+            | (i32::from(bytes[1]) << 8)
+            | (i32::from(bytes[2]) << 16)
+            | (i32::from(bytes[3]) << 24),
+    )
+}
+
+/// Returns the numeric capability at the given index.
+///
+/// Negative values mean the capability is absent or cancelled.
+fn number(data: &[u8], index: usize) -> Option<i32> {
+    let size = match read_i16(data, 0)? {
+        MAGIC => 2,
+        MAGIC_32 => 4,
+        _ => return None,
+    };
+    let names_size = read_i16(data, 2)? as usize;
+    let bools_count = read_i16(data, 4)? as usize;
+    let numbers_count = read_i16(data, 6)? as usize;
+
+    if index >= numbers_count {
+        return None;
+    }
+
+    // The numbers section is aligned on an even offset.
+    let mut offset = 12 + names_size + bools_count;
+    offset += offset % 2;
+    offset += index * size;
+
+    if size == 2 {
+        read_i16(data, offset).map(i32::from)
+    } else {
+        read_i32(data, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(magic: i16, colors: &[u8]) -> Vec<u8> {
+        let size = if magic == MAGIC { 2 } else { 4 };
+        let names = b"test|Test terminal\0";
+        let bools = [1u8, 0, 1];
+
+        let mut data = Vec::new();
+        for &n in &[magic, names.len() as i16, bools.len() as i16, 14, 0, 0]
+        {
+            data.push(n as u8);
+            data.push((n >> 8) as u8);
+        }
+        data.extend_from_slice(names);
+        data.extend_from_slice(&bools);
+        if data.len() % 2 != 0 {
+            data.push(0);
+        }
+        for _ in 0..COLORS {
+            data.extend(vec![0xFF; size]);
+        }
+        data.extend_from_slice(colors);
+        data
+    }
+
+    #[test]
+    fn reads_colors() {
+        let data = entry(MAGIC, &[0, 1]);
+        assert_eq!(number(&data, COLORS), Some(256));
+
+        let data = entry(MAGIC_32, &[0, 0, 0, 1]);
+        assert_eq!(number(&data, COLORS), Some(1 << 24));
+
+        let data = entry(MAGIC, &[0xFF, 0xFF]);
+        assert_eq!(number(&data, COLORS), Some(-1));
+        assert_eq!(number(&data, 20), None);
+    }
+}
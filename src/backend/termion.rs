@@ -1,32 +1,235 @@
+//! Backend using the pure-rust termion library.
+
 extern crate termion;
 
 extern crate chan_signal;
+extern crate libc;
 
 use self::termion::color as tcolor;
 use self::termion::event::Event as TEvent;
 use self::termion::event::Key as TKey;
 use self::termion::event::MouseButton as TMouseButton;
 use self::termion::event::MouseEvent as TMouseEvent;
+use self::termion::cursor::DetectCursorPos;
 use self::termion::input::{MouseTerminal, TermRead};
 use self::termion::raw::{IntoRawMode, RawTerminal};
-use self::termion::screen::AlternateScreen;
 use self::termion::style as tstyle;
 use backend;
 use chan;
-use event::{Event, Key, MouseButton, MouseEvent};
+use event::{Event, Key, Modifiers, MouseButton, MouseEvent};
 use std::cell::Cell;
-use std::io::{Stdout, Write};
+use std::io::{self, Read, Stdout, Write};
+use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::time::{Duration, Instant};
 use theme;
 use vec::Vec2;
 
 pub struct Concrete {
-    terminal: AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>,
+    terminal: MouseTerminal<RawTerminal<Stdout>>,
+    // Set in inline mode, instead of using the alternate screen.
+    inline: Option<Inline>,
     current_style: Cell<theme::ColorPair>,
     input: chan::Receiver<TEvent>,
     resize: chan::Receiver<chan_signal::Signal>,
+    wake: chan::Receiver<()>,
+    waker: Arc<ChanWaker>,
     timeout: Option<u32>,
     last_button: Option<MouseButton>,
+    // Set while suspended, so the input thread leaves stdin alone.
+    input_paused: Arc<AtomicBool>,
+    // Write end of the pipe waking up the input thread when paused or
+    // resumed.
+    input_wake_fd: libc::c_int,
+    capabilities: backend::Capabilities,
+    // Cursor shape last sent to the terminal, if any.
+    cursor_shape: Cell<Option<backend::CursorShape>>,
+    // Set while the screen flashes: when to switch back to normal video.
+    flash_end: Option<Instant>,
+}
+
+/// Lines used by the backend in inline mode.
+#[derive(Clone, Copy, Debug)]
+struct Inline {
+    /// First line of the region, starting at 1.
+    row: u16,
+    /// Number of lines in the region.
+    height: u16,
+}
+
+impl Inline {
+    /// Reserves `height` lines under the cursor, scrolling if needed.
+    fn reserve<W: Write>(terminal: &mut W, height: usize) -> io::Result<Self> {
+        let (_, rows) = termion::terminal_size()?;
+        let height = height.max(1).min(usize::from(rows)) as u16;
+
+        // Start on a new line if the cursor isn't at the beginning of one.
+        let (x, _) = terminal.cursor_pos()?;
+        if x > 1 {
+            write!(terminal, "\r\n")?;
+        }
+        for _ in 1..height {
+            writeln!(terminal)?;
+        }
+
+        let (_, bottom) = terminal.cursor_pos()?;
+        Ok(Inline {
+            row: bottom + 1 - height,
+            height,
+        })
+    }
+
+    /// Moves the cursor to the beginning of the line after the region.
+    ///
+    /// Everything drawn in the region is left on screen.
+    fn leave(&self) {
+        print!(
+            "{}{}\r\n",
+            tstyle::Reset,
+            termion::cursor::Goto(1, self.row + self.height - 1)
+        );
+    }
+}
+
+/// Reads from stdin, except while paused.
+///
+/// This lets other programs use the terminal while we're suspended.
+struct PausableStdin {
+    // Always stdin, except in tests.
+    fd: libc::c_int,
+    paused: Arc<AtomicBool>,
+    // Read end of a pipe, written to when `paused` changes.
+    wake_fd: libc::c_int,
+}
+
+impl PausableStdin {
+    /// Empties the wake-up pipe.
+    ///
+    /// Returns `false` if the write end was closed.
+    fn drain_wake_fd(&self) -> bool {
+        let mut buffer = [0u8; 64];
+        loop {
+            let n = unsafe {
+                libc::read(
+                    self.wake_fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            if n == 0 {
+                return false;
+            }
+            if n < 0 {
+                return true;
+            }
+        }
+    }
+}
+
+impl Drop for PausableStdin {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.wake_fd);
+        }
+    }
+}
+
+impl Read for PausableStdin {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            // Don't block in `read`, so we notice when we get paused.
+            // While paused, stdin is left out (negative fds are ignored).
+            let stdin = if self.paused.load(Ordering::SeqCst) {
+                -1
+            } else {
+                self.fd
+            };
+            let mut fds = [
+                libc::pollfd {
+                    fd: self.wake_fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: stdin,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            if unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) } < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(error);
+            }
+
+            if fds[0].revents != 0 {
+                if !self.drain_wake_fd() {
+                    // The backend is gone.
+                    return Ok(0);
+                }
+                // Check again if we're paused.
+                continue;
+            }
+            if fds[1].revents == 0 {
+                continue;
+            }
+
+            // `io::stdin()` is buffered: it would read more than we ask
+            // for, and we would then wait for input we already have.
+            let n = unsafe {
+                libc::read(
+                    self.fd,
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                )
+            };
+            if n >= 0 {
+                return Ok(n as usize);
+            }
+
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+}
+
+/// Creates a pipe, returning its read and write ends.
+///
+/// Neither end blocks.
+fn nonblocking_pipe() -> io::Result<[libc::c_int; 2]> {
+    let mut pipe = [0; 2];
+    unsafe {
+        if libc::pipe(pipe.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        for &fd in &pipe {
+            let flags = libc::fcntl(fd, libc::F_GETFL);
+            libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK);
+        }
+    }
+    Ok(pipe)
+}
+
+/// Wakes up `poll_event` through a channel.
+struct ChanWaker {
+    sender: chan::Sender<()>,
+    // Set while a wake-up is waiting to be received.
+    pending: AtomicBool,
+}
+
+impl backend::Waker for ChanWaker {
+    fn wake(&self) {
+        // Only queue a single wake-up at a time.
+        if !self.pending.swap(true, Ordering::SeqCst) {
+            self.sender.send(());
+        }
+    }
 }
 
 trait Effectable {
@@ -57,13 +260,278 @@ impl Effectable for theme::Effect {
 }
 
 impl Concrete {
+    /// Gives the cursor its default shape back, if we changed it.
+    fn reset_cursor_shape(&self) {
+        if self.cursor_shape.take().is_some() {
+            print!("{}", backend::CURSOR_SHAPE_RESET);
+        }
+    }
+
+    /// Tells the input thread to stop or start reading stdin.
+    fn pause_input(&self, paused: bool) {
+        self.input_paused.store(paused, Ordering::SeqCst);
+        let byte = 0u8;
+        // If the pipe is full, a wake-up is already pending anyway.
+        unsafe {
+            libc::write(
+                self.input_wake_fd,
+                &byte as *const u8 as *const libc::c_void,
+                1,
+            );
+        }
+    }
+
+    /// Creates a new backend, using the whole screen or only a few lines.
+    fn init_with(
+        height: Option<usize>
+    ) -> Result<Box<Self>, backend::Error> {
+        if !termion::is_tty(&io::stdin()) || !termion::is_tty(&io::stdout()) {
+            return Err(backend::Error::NotATty);
+        }
+
+
+        // After being stopped, we need to redraw everything, just like
+        // after a resize.
+        let resize = chan_signal::notify(&[
+            chan_signal::Signal::WINCH,
+            chan_signal::Signal::CONT,
+        ]);
+
+        // TODO: lock stdout
+        let mut terminal =
+            MouseTerminal::from(::std::io::stdout().into_raw_mode()?);
+
+        // We read the cursor position before the input thread starts.
+        let inline = match height {
+            Some(height) => Some(Inline::reserve(&mut terminal, height)?),
+            None => {
+                print!("{}", termion::screen::ToAlternateScreen);
+                None
+            }
+        };
+
+        print!("{}", termion::cursor::Hide);
+
+        // Report mouse moves even when no button is pressed.
+        print!("\x1B[?1003h");
+
+        // Enable bracketed paste.
+        print!("\x1B[?2004h");
+        // Enable focus reporting.
+        print!("\x1B[?1004h");
+
+        let (sender, receiver) = chan::async();
+        let (wake_sender, wake) = chan::async();
+
+        // Used to wake up the input thread when it gets paused.
+        let pipe = nonblocking_pipe()?;
+
+        let input_paused = Arc::new(AtomicBool::new(false));
+        let stdin = PausableStdin {
+            fd: libc::STDIN_FILENO,
+            paused: Arc::clone(&input_paused),
+            wake_fd: pipe[0],
+        };
+
+        thread::spawn(move || {
+            for key in stdin.events() {
+                if let Ok(key) = key {
+                    sender.send(key)
+                }
+            }
+        });
+
+        let c = Concrete {
+            terminal: terminal,
+            inline: inline,
+            current_style: Cell::new(theme::ColorPair::from_256colors(0, 0)),
+            input: receiver,
+            resize: resize,
+            wake: wake,
+            waker: Arc::new(ChanWaker {
+                sender: wake_sender,
+                pending: AtomicBool::new(false),
+            }),
+            timeout: None,
+            last_button: None,
+            input_paused: input_paused,
+            input_wake_fd: pipe[1],
+            capabilities: backend::Capabilities {
+                color_depth: detect_color_depth(),
+                // We write UTF-8 no matter what, so we assume the terminal
+                // reads it, unless told otherwise.
+                box_drawing: backend::utf8_locale().unwrap_or(true),
+                cursor: true,
+                ..backend::Capabilities::default()
+            },
+            cursor_shape: Cell::new(None),
+            flash_end: None,
+        };
+
+        Ok(Box::new(c))
+    }
+
+    /// Creates a new backend drawing on `height` lines under the cursor,
+    /// instead of taking over the whole screen.
+    ///
+    /// Lines are added at the bottom of the terminal if needed. When the
+    /// application exits, the last frame stays in the scrollback, and the
+    /// shell continues on the next line.
+    ///
+    /// This is useful for small prompts in command-line tools:
+    ///
+    /// ```rust,ignore
+    /// let backend = cursive::backend::termion::Concrete::init_inline(5)?;
+    /// let mut siv = Cursive::with_backend(backend);
+    /// ```
+    pub fn init_inline(height: usize) -> Result<Box<Self>, backend::Error> {
+        Concrete::init_with(Some(height))
+    }
+
+    /// Returns the terminal position of the given screen cell.
+    fn goto(&self, x: usize, y: usize) -> termion::cursor::Goto {
+        let top = self.inline.map_or(1, |inline| inline.row);
+        termion::cursor::Goto(1 + x as u16, top + y as u16)
+    }
+
+    /// Makes mouse positions relative to the region, in inline mode.
+    ///
+    /// Events above the region are dropped.
+    fn relative_mouse(&self, event: Event) -> Event {
+        match (event, self.inline) {
+            (
+                Event::Mouse {
+                    event,
+                    position,
+                    offset,
+                },
+                Some(inline),
+            ) => {
+                let top = usize::from(inline.row - 1);
+                if position.y < top {
+                    return Event::Unknown(vec![]);
+                }
+                Event::Mouse {
+                    event,
+                    position: Vec2::new(position.x, position.y - top),
+                    offset,
+                }
+            }
+            (event, _) => event,
+        }
+    }
+
     fn apply_colors(&self, colors: theme::ColorPair) {
-        with_color(&colors.front, |c| print!("{}", tcolor::Fg(c)));
-        with_color(&colors.back, |c| print!("{}", tcolor::Bg(c)));
+        let depth = self.capabilities.color_depth;
+        with_color(&colors.front, depth, |c| print!("{}", tcolor::Fg(c)));
+        with_color(&colors.back, depth, |c| print!("{}", tcolor::Bg(c)));
     }
+    /// Switches back to normal video, if the screen is flashing.
+    fn stop_flash(&mut self) {
+        if self.flash_end.take().is_some() {
+            print!("\x1B[?5l");
+            self.terminal.flush().unwrap();
+        }
+    }
+
+    /// Returns the next event, waiting at most `timeout` milliseconds.
+    ///
+    /// While the screen flashes, it doesn't wait past the end of the flash.
+    fn poll_event_within(&mut self, timeout: Option<u32>) -> Event {
+        let timeout = match self.flash_end {
+            Some(end) => {
+                let now = Instant::now();
+                if end <= now {
+                    self.stop_flash();
+                    timeout
+                } else {
+                    let left = backend::timeout_ms(end - now) as u32;
+                    Some(timeout.map_or(left, |timeout| timeout.min(left)))
+                }
+            }
+            None => timeout,
+        };
+
+        let result;
+        {
+            let input = &self.input;
+            let resize = &self.resize;
+            let wake = &self.wake;
+            let waker = &self.waker;
+
+            if let Some(timeout) = timeout {
+                let timeout = chan::after_ms(timeout);
+                chan_select!{
+                    timeout.recv() => return Event::Refresh,
+                    wake.recv() => {
+                        waker.pending.store(false, Ordering::SeqCst);
+                        return Event::Refresh;
+                    },
+                    resize.recv() => return Event::WindowResize,
+                    input.recv() -> input => result = Some(input.unwrap()),
+                }
+            } else {
+                chan_select!{
+                    wake.recv() => {
+                        waker.pending.store(false, Ordering::SeqCst);
+                        return Event::Refresh;
+                    },
+                    resize.recv() => return Event::WindowResize,
+                    input.recv() -> input => result = Some(input.unwrap()),
+                }
+            }
+        }
+
+        let event = self.map_key(result.unwrap());
+        self.relative_mouse(event)
+    }
+
+    /// Reads pasted text, until the end of the bracketed paste.
+    fn read_paste(&mut self) -> Event {
+        let mut text = String::new();
+
+        loop {
+            let event;
+            {
+                let input = &self.input;
+                let timeout = chan::after_ms(backend::PASTE_TIMEOUT);
+                chan_select!{
+                    timeout.recv() => break,
+                    input.recv() -> input => event = input,
+                }
+            }
+
+            match event {
+                Some(TEvent::Key(TKey::Char(c))) => text.push(c),
+                Some(TEvent::Unsupported(ref bytes))
+                    if *bytes == backend::PASTE_END =>
+                {
+                    break
+                }
+                Some(_) => (),
+                None => break,
+            }
+        }
+
+        Event::Paste(text)
+    }
+
     fn map_key(&mut self, event: TEvent) -> Event {
         match event {
-            TEvent::Unsupported(bytes) => Event::Unknown(bytes),
+            TEvent::Unsupported(ref bytes)
+                if *bytes == backend::PASTE_START =>
+            {
+                self.read_paste()
+            }
+            TEvent::Unsupported(ref bytes) if *bytes == backend::FOCUS_IN => {
+                Event::FocusIn
+            }
+            TEvent::Unsupported(ref bytes) if *bytes == backend::FOCUS_OUT => {
+                Event::FocusOut
+            }
+            TEvent::Unsupported(bytes) => parse_modified_key(&bytes)
+                .or_else(|| parse_mouse_move(&bytes))
+                .unwrap_or(Event::Unknown(bytes)),
             TEvent::Key(TKey::Esc) => Event::Key(Key::Esc),
             TEvent::Key(TKey::Backspace) => Event::Key(Key::Backspace),
             TEvent::Key(TKey::Left) => Event::Key(Key::Left),
@@ -82,8 +550,16 @@ impl Concrete {
             TEvent::Key(TKey::Char('\t')) => Event::Key(Key::Tab),
             TEvent::Key(TKey::Char(c)) => Event::Char(c),
             TEvent::Key(TKey::Ctrl('c')) => Event::Exit,
-            TEvent::Key(TKey::Ctrl(c)) => Event::CtrlChar(c),
-            TEvent::Key(TKey::Alt(c)) => Event::AltChar(c),
+            TEvent::Key(TKey::BackTab) => {
+                Event::ModKey(Modifiers::SHIFT, Key::Tab)
+            }
+            TEvent::Key(TKey::Ctrl(c)) => Event::ModChar(Modifiers::CTRL, c),
+            // Alt+Ctrl+letter comes as Alt with a control character.
+            TEvent::Key(TKey::Alt(c)) if '\u{1}' <= c && c <= '\u{1a}' => {
+                let c = (b'a' - 1 + c as u8) as char;
+                Event::ModChar(Modifiers::CTRL_ALT, c)
+            }
+            TEvent::Key(TKey::Alt(c)) => Event::ModChar(Modifiers::ALT, c),
             TEvent::Mouse(TMouseEvent::Press(btn, x, y)) => {
                 let position = (x - 1, y - 1).into();
 
@@ -137,46 +613,104 @@ impl Concrete {
 }
 
 impl backend::Backend for Concrete {
-    fn init() -> Box<Self> {
-        print!("{}", termion::cursor::Hide);
+    fn init() -> Result<Box<Self>, backend::Error> {
+        Concrete::init_with(None)
+    }
 
-        let resize = chan_signal::notify(&[chan_signal::Signal::WINCH]);
+    fn finish(&mut self) {
+        self.stop_flash();
+        self.reset_cursor_shape();
+        print!("\x1B[?1003l");
+        print!("\x1B[?2004l");
+        print!("\x1B[?1004l");
+        if let Some(inline) = self.inline {
+            inline.leave();
+            print!("{}", termion::cursor::Show);
+        } else {
+            print!(
+                "{}{}",
+                termion::cursor::Show,
+                termion::cursor::Goto(1, 1)
+            );
+            print!(
+                "{}[49m{}[39m{}",
+                27 as char,
+                27 as char,
+                termion::clear::All
+            );
+            print!("{}", termion::screen::ToMainScreen);
+        }
+        self.terminal.flush().unwrap();
+    }
 
-        // TODO: lock stdout
-        let terminal = AlternateScreen::from(MouseTerminal::from(
-            ::std::io::stdout().into_raw_mode().unwrap(),
-        ));
+    fn suspend(&mut self) {
+        self.pause_input(true);
 
-        let (sender, receiver) = chan::async();
+        self.stop_flash();
+        self.reset_cursor_shape();
+        print!("\x1B[?2004l");
+        print!("\x1B[?1004l");
+        print!("\x1B[?1006l\x1B[?1015l\x1B[?1003l\x1B[?1000l");
+        match self.inline {
+            Some(inline) => inline.leave(),
+            None => print!("{}", termion::screen::ToMainScreen),
+        }
+        print!("{}", termion::cursor::Show);
+        self.terminal.flush().unwrap();
+        self.terminal.suspend_raw_mode().unwrap();
+    }
 
-        thread::spawn(move || {
-            for key in ::std::io::stdin().events() {
-                if let Ok(key) = key {
-                    sender.send(key)
+    fn resume(&mut self) {
+        self.terminal.activate_raw_mode().unwrap();
+        match self.inline {
+            // Other programs may have written below us: start over on new
+            // lines. The input thread is still paused, so we can read the
+            // cursor position.
+            Some(inline) => {
+                let height = usize::from(inline.height);
+                if let Ok(inline) = Inline::reserve(&mut self.terminal, height)
+                {
+                    self.inline = Some(inline);
                 }
             }
-        });
+            None => print!("{}", termion::screen::ToAlternateScreen),
+        }
+        print!("{}", termion::cursor::Hide);
+        print!("\x1B[?1000h\x1B[?1003h\x1B[?1015h\x1B[?1006h");
+        print!("\x1B[?2004h");
+        print!("\x1B[?1004h");
+        self.terminal.flush().unwrap();
 
-        let c = Concrete {
-            terminal: terminal,
-            current_style: Cell::new(theme::ColorPair::from_256colors(0, 0)),
-            input: receiver,
-            resize: resize,
-            timeout: None,
-            last_button: None,
-        };
+        self.pause_input(false);
+    }
 
-        Box::new(c)
+    fn stop_process(&mut self) {
+        unsafe {
+            libc::raise(libc::SIGTSTP);
+        }
     }
 
-    fn finish(&mut self) {
-        print!("{}{}", termion::cursor::Show, termion::cursor::Goto(1, 1));
-        print!(
-            "{}[49m{}[39m{}",
-            27 as char,
-            27 as char,
-            termion::clear::All
-        );
+    fn set_title(&mut self, title: &str) {
+        print!("{}", backend::xterm_title(title));
+        self.terminal.flush().unwrap();
+    }
+
+    fn bell(&mut self) {
+        print!("\x07");
+        self.terminal.flush().unwrap();
+    }
+
+    fn flash(&mut self) {
+        // Briefly switch to reverse video. Switching back is done while
+        // polling events, so the event loop doesn't block meanwhile.
+        print!("\x1B[?5h");
+        self.terminal.flush().unwrap();
+        self.flash_end = Some(Instant::now() + Duration::from_millis(100));
+    }
+
+    fn set_clipboard(&mut self, text: &str) {
+        print!("{}", backend::osc52_copy(text));
+        self.terminal.flush().unwrap();
     }
 
     fn set_color(&self, color: theme::ColorPair) -> theme::ColorPair {
@@ -199,12 +733,26 @@ impl backend::Backend for Concrete {
     }
 
     fn has_colors(&self) -> bool {
-        // TODO: color support detection?
         true
     }
 
+    fn color_depth(&self) -> theme::ColorDepth {
+        self.capabilities.color_depth
+    }
+
+    fn capabilities(&self) -> backend::Capabilities {
+        self.capabilities.clone()
+    }
+
     fn screen_size(&self) -> (usize, usize) {
         let (x, y) = termion::terminal_size().unwrap_or((1, 1));
+        let y = match self.inline {
+            // The terminal may have shrunk since we reserved the lines.
+            Some(inline) => {
+                inline.height.min((y + 1).saturating_sub(inline.row))
+            }
+            None => y,
+        };
         (x as usize, y as usize)
     }
 
@@ -213,7 +761,18 @@ impl backend::Backend for Concrete {
             front: color,
             back: color,
         });
-        print!("{}", termion::clear::All);
+        match self.inline {
+            Some(inline) => {
+                for y in 0..inline.height {
+                    print!(
+                        "{}{}",
+                        termion::cursor::Goto(1, inline.row + y),
+                        termion::clear::CurrentLine
+                    );
+                }
+            }
+            None => print!("{}", termion::clear::All),
+        }
     }
 
     fn refresh(&mut self) {
@@ -221,11 +780,21 @@ impl backend::Backend for Concrete {
     }
 
     fn print_at(&self, (x, y): (usize, usize), text: &str) {
-        print!(
-            "{}{}",
-            termion::cursor::Goto(1 + x as u16, 1 + y as u16),
-            text
-        );
+        print!("{}{}", self.goto(x, y), text);
+    }
+
+    fn show_cursor(
+        &self, (x, y): (usize, usize), shape: backend::CursorShape
+    ) {
+        if self.cursor_shape.get() != Some(shape) {
+            print!("{}", shape.xterm_sequence());
+            self.cursor_shape.set(Some(shape));
+        }
+        print!("{}{}", self.goto(x, y), termion::cursor::Show);
+    }
+
+    fn hide_cursor(&self) {
+        print!("{}", termion::cursor::Hide);
     }
 
     fn set_refresh_rate(&mut self, fps: u32) {
@@ -233,31 +802,137 @@ impl backend::Backend for Concrete {
     }
 
     fn poll_event(&mut self) -> Event {
-        let result;
-        {
-            let input = &self.input;
-            let resize = &self.resize;
+        let timeout = self.timeout;
+        self.poll_event_within(timeout)
+    }
 
-            if let Some(timeout) = self.timeout {
-                let timeout = chan::after_ms(timeout);
-                chan_select!{
-                    timeout.recv() => return Event::Refresh,
-                    resize.recv() => return Event::WindowResize,
-                    input.recv() -> input => result = Some(input.unwrap()),
-                }
-            } else {
-                chan_select!{
-                    resize.recv() => return Event::WindowResize,
-                    input.recv() -> input => result = Some(input.unwrap()),
-                }
-            }
+    fn poll_event_timeout(&mut self, timeout: Duration) -> Event {
+        let timeout = backend::timeout_ms(timeout)
+            .min(u64::from(u32::max_value())) as u32;
+        let timeout = match self.timeout {
+            Some(current) => current.min(timeout),
+            None => timeout,
+        };
+        self.poll_event_within(Some(timeout))
+    }
+
+    fn waker(&self) -> Option<Arc<backend::Waker>> {
+        Some(Arc::clone(&self.waker) as Arc<backend::Waker>)
+    }
+}
+
+impl Drop for Concrete {
+    fn drop(&mut self) {
+        // The input thread stops when it sees the pipe closed.
+        unsafe {
+            libc::close(self.input_wake_fd);
         }
+    }
+}
+
+/// Parses xterm sequences for keys with modifiers, like `ESC [ 1 ; 5 D`.
+///
+/// termion doesn't recognize these, and reports them as unsupported.
+fn parse_modified_key(bytes: &[u8]) -> Option<Event> {
+    if !bytes.starts_with(b"\x1B[") {
+        return None;
+    }
+    let (&last, params) = bytes[2..].split_last()?;
+    let params = str::from_utf8(params).ok()?;
 
-        self.map_key(result.unwrap())
+    let mut params = params.split(';');
+    let code: u8 = params.next()?.parse().ok()?;
+    let modifiers = backend::xterm_modifiers(params.next()?.parse().ok()?)?;
+    if params.next().is_some() {
+        return None;
     }
+
+    let key = match (last, code) {
+        (b'A', 1) => Key::Up,
+        (b'B', 1) => Key::Down,
+        (b'C', 1) => Key::Right,
+        (b'D', 1) => Key::Left,
+        (b'H', 1) => Key::Home,
+        (b'F', 1) => Key::End,
+        (b'P'..=b'S', 1) => Key::from_f(1 + last - b'P'),
+        (b'~', 2) => Key::Ins,
+        (b'~', 3) => Key::Del,
+        (b'~', 5) => Key::PageUp,
+        (b'~', 6) => Key::PageDown,
+        (b'~', 15) => Key::F5,
+        (b'~', 17..=21) => Key::from_f(code - 11),
+        (b'~', 23..=24) => Key::from_f(code - 12),
+        _ => return None,
+    };
+
+    Some(Event::ModKey(modifiers, key))
 }
 
-fn with_color<F, R>(clr: &theme::Color, f: F) -> R
+/// Parses SGR mouse moves, like `ESC [ < 35 ; 10 ; 4 M`.
+///
+/// termion only recognizes drags with the left button, and reports other
+/// moves as unsupported.
+fn parse_mouse_move(bytes: &[u8]) -> Option<Event> {
+    if !bytes.starts_with(b"\x1B[<") || !bytes.ends_with(b"M") {
+        return None;
+    }
+    let params = str::from_utf8(&bytes[3..bytes.len() - 1]).ok()?;
+
+    let mut params = params.split(';');
+    let code: u16 = params.next()?.parse().ok()?;
+    let x: usize = params.next()?.parse().ok()?;
+    let y: usize = params.next()?.parse().ok()?;
+    if params.next().is_some() || code & 32 == 0 || x == 0 || y == 0 {
+        return None;
+    }
+
+    // Ignore the modifiers: only keep the button.
+    let event = match code & !(32 | 4 | 8 | 16) {
+        0 => MouseEvent::Hold(MouseButton::Left),
+        1 => MouseEvent::Hold(MouseButton::Middle),
+        2 => MouseEvent::Hold(MouseButton::Right),
+        3 => MouseEvent::Move,
+        _ => return None,
+    };
+
+    Some(Event::Mouse {
+        event,
+        position: Vec2::new(x - 1, y - 1),
+        offset: Vec2::zero(),
+    })
+}
+
+/// Guesses the colors supported by the terminal.
+///
+/// `COLORTERM` tells us about true-color support. Otherwise, the terminfo
+/// entry for `TERM` is used, or the name of the terminal itself.
+fn detect_color_depth() -> theme::ColorDepth {
+    if let Ok(colorterm) = ::std::env::var("COLORTERM") {
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return theme::ColorDepth::TrueColor;
+        }
+    }
+
+    let term = ::std::env::var("TERM").unwrap_or_default();
+    if let Some(colors) = backend::terminfo::colors(&term) {
+        return theme::ColorDepth::from_colors(colors);
+    }
+
+    if term.ends_with("-direct") {
+        theme::ColorDepth::TrueColor
+    } else if term.contains("256color") {
+        theme::ColorDepth::Colors256
+    } else if term.contains("16color") {
+        theme::ColorDepth::Colors16
+    } else {
+        theme::ColorDepth::Colors8
+    }
+}
+
+/// Calls `f` with the termion version of the given color.
+///
+/// Without true-color support, `Rgb` colors use the 256 colors list.
+fn with_color<F, R>(clr: &theme::Color, depth: theme::ColorDepth, f: F) -> R
 where
     F: FnOnce(&tcolor::Color) -> R,
 {
@@ -285,9 +960,74 @@ where
         theme::Color::Light(theme::BaseColor::Cyan) => f(&tcolor::LightCyan),
         theme::Color::Light(theme::BaseColor::White) => f(&tcolor::LightWhite),
 
-        theme::Color::Rgb(r, g, b) => f(&tcolor::Rgb(r, g, b)),
+        theme::Color::Rgb(r, g, b)
+            if depth == theme::ColorDepth::TrueColor =>
+        {
+            f(&tcolor::Rgb(r, g, b))
+        }
+        theme::Color::Rgb(..) => {
+            let n = clr.to_256colors().unwrap_or(0);
+            f(&tcolor::AnsiValue(n))
+        }
         theme::Color::RgbLowRes(r, g, b) => {
             f(&tcolor::AnsiValue::rgb(r, g, b))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn write_byte(fd: libc::c_int, byte: u8) {
+        let n = unsafe {
+            libc::write(fd, &byte as *const u8 as *const libc::c_void, 1)
+        };
+        assert_eq!(n, 1);
+    }
+
+    #[test]
+    fn pausable_stdin() {
+        let input = nonblocking_pipe().unwrap();
+        let wake = nonblocking_pipe().unwrap();
+        let paused = Arc::new(AtomicBool::new(true));
+        let mut stdin = PausableStdin {
+            fd: input[0],
+            paused: Arc::clone(&paused),
+            wake_fd: wake[0],
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 8];
+            while let Ok(n) = stdin.read(&mut buffer) {
+                sender.send(buffer[..n].to_vec()).unwrap();
+                if n == 0 {
+                    break;
+                }
+            }
+        });
+
+        // Nothing is read while paused.
+        write_byte(input[1], b'a');
+        let timeout = Duration::from_millis(50);
+        assert!(receiver.recv_timeout(timeout).is_err());
+
+        paused.store(false, Ordering::SeqCst);
+        write_byte(wake[1], 0);
+        let timeout = Duration::from_secs(5);
+        assert_eq!(receiver.recv_timeout(timeout), Ok(b"a".to_vec()));
+
+        // Closing the pipe stops the reader.
+        unsafe {
+            libc::close(wake[1]);
+        }
+        assert_eq!(receiver.recv_timeout(timeout), Ok(Vec::new()));
+
+        unsafe {
+            libc::close(input[0]);
+            libc::close(input[1]);
+        }
+    }
+}
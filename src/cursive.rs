@@ -1,12 +1,14 @@
 use backend;
 use backend::Backend;
-use event::{Callback, Event, EventResult};
+use event::{Callback, ClickCounter, Event, EventResult, Modifiers};
 use printer::Printer;
 use std::any::Any;
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 use theme;
+use timer::{Action, TimerHandle, Timers};
 use vec::Vec2;
 use view::{self, Finder, View};
 use views;
@@ -14,6 +16,51 @@ use views;
 /// Identifies a screen in the cursive root.
 pub type ScreenId = usize;
 
+/// Asynchronous callback function trait.
+///
+/// Every `FnOnce(&mut Cursive) -> () + Send` automatically
+/// implements this.
+///
+/// This is a workaround only because `Box<FnOnce()>` is not
+/// working and `FnBox` is unstable.
+pub trait CbFunc: Send {
+    /// Calls the function.
+    fn call_box(self: Box<Self>, siv: &mut Cursive);
+}
+
+impl<F: FnOnce(&mut Cursive) -> () + Send> CbFunc for F {
+    fn call_box(self: Box<Self>, siv: &mut Cursive) {
+        (*self)(siv)
+    }
+}
+
+/// Sender for asynchronous callbacks.
+///
+/// Obtained with [`Cursive::cb_sink`]. It can be cloned and sent to other
+/// threads.
+///
+/// [`Cursive::cb_sink`]: struct.Cursive.html#method.cb_sink
+#[derive(Clone)]
+pub struct CbSink {
+    sender: mpsc::Sender<Box<CbFunc>>,
+    waker: Option<Arc<backend::Waker>>,
+}
+
+impl CbSink {
+    /// Sends a callback to be run on the next event cycle.
+    ///
+    /// If the backend supports it, this wakes up the event loop.
+    pub fn send(
+        &self, cb: Box<CbFunc>
+    ) -> Result<(), mpsc::SendError<Box<CbFunc>>> {
+        self.sender.send(cb)?;
+        if let Some(ref waker) = self.waker {
+            waker.wake();
+        }
+        Ok(())
+    }
+}
+
 /// Central part of the cursive library.
 ///
 /// It initializes ncurses on creation and cleans up on drop.
@@ -34,27 +81,92 @@ pub struct Cursive {
 
     backend: Box<backend::Backend>,
 
-    cb_source: mpsc::Receiver<Box<Callback>>,
-    cb_sink: mpsc::Sender<Box<Callback>>,
+    // Last known position of the mouse, used to draw hovered views.
+    mouse_position: Option<Vec2>,
+    clicks: ClickCounter,
+
+    cb_source: mpsc::Receiver<Box<CbFunc>>,
+    cb_sink: CbSink,
+
+    timers: Timers,
 }
 
 new_default!(Cursive);
 
 impl Cursive {
     /// Creates a new Cursive root, and initialize the back-end.
+    ///
+    /// # Panics
+    ///
+    /// If the backend could not be initialized.
+    /// Use [`try_new`] to handle this case.
+    ///
+    /// [`try_new`]: #method.try_new
     pub fn new() -> Self {
-        let backend = backend::Concrete::init();
-        Cursive::with_backend(backend)
+        match Cursive::try_new() {
+            Ok(siv) => siv,
+            Err(e) => panic!("Could not initialize the backend: {}", e),
+        }
+    }
+
+    /// Creates a new Cursive root, and initialize the back-end.
+    ///
+    /// When several backends are enabled, the `CURSIVE_BACKEND` environment
+    /// variable selects one by name. Otherwise, the first one that works
+    /// is used (see [`backend::init_default`]).
+    ///
+    /// Returns an error if the terminal cannot be used, for instance when
+    /// the output is not a terminal. The terminal is left untouched in
+    /// this case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # extern crate cursive;
+    /// # use cursive::Cursive;
+    /// # use cursive::views::TextView;
+    /// # fn main() {
+    /// match Cursive::try_new() {
+    ///     Ok(mut siv) => {
+    ///         siv.root_mut()
+    ///             .add_fullscreen_layer(TextView::new("Hello World!"));
+    ///         siv.run();
+    ///     }
+    ///     Err(_) => println!("Hello World!"),
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`backend::init_default`]: backend/fn.init_default.html
+    pub fn try_new() -> Result<Self, backend::Error> {
+        let mut backend = backend::init_default()?;
+
+        let (x, y) = backend.screen_size();
+        if x == 0 || y == 0 {
+            backend.finish();
+            return Err(backend::Error::TooSmall(Vec2::new(x, y)));
+        }
+
+        Ok(Cursive::with_backend(backend))
     }
 
-    /// This function grows breasts on catgurls
+    /// Creates a new Cursive root using the given backend.
+    ///
+    /// The backend is wrapped in a [`Buffered`] layer, so only the cells
+    /// that changed since the last frame are sent to it.
+    ///
+    /// [`Buffered`]: backend/struct.Buffered.html
     pub fn with_backend(backend: Box<Backend>) -> Self {
-        
+        let backend = Box::new(backend::Buffered::new(backend));
         let theme = theme::load_default();
         // theme.activate(&mut backend);
         // let theme = theme::load_theme("assets/style.toml").unwrap();
 
         let (tx, rx) = mpsc::channel();
+        let cb_sink = CbSink {
+            sender: tx,
+            waker: backend.waker(),
+        };
 
         Cursive {
             theme: theme,
@@ -63,8 +175,11 @@ impl Cursive {
             global_callbacks: HashMap::new(),
             running: true,
             cb_source: rx,
-            cb_sink: tx,
+            cb_sink: cb_sink,
+            timers: Timers::new(),
             backend: backend,
+            mouse_position: None,
+            clicks: ClickCounter::new(),
         }
     }
 
@@ -75,9 +190,12 @@ impl Cursive {
     ///
     /// Callbacks will be executed in the order
     /// of arrival on the next event cycle.
+    /// Each callback is run exactly once.
     ///
-    /// Note that you currently need to call [`set_fps`] to force cursive to
-    /// regularly check for messages.
+    /// With the ncurses and termion backends, sending a callback wakes up
+    /// the event loop, so it is processed right away. With other backends,
+    /// you need to call [`set_fps`] to force cursive to regularly check for
+    /// messages.
     ///
     /// # Examples
     ///
@@ -86,7 +204,6 @@ impl Cursive {
     /// # use cursive::*;
     /// # fn main() {
     /// let mut siv = Cursive::new();
-    /// siv.set_fps(10);
     ///
     /// // quit() will be called during the next event cycle
     /// siv.cb_sink().send(Box::new(|s: &mut Cursive| s.quit()));
@@ -94,10 +211,71 @@ impl Cursive {
     /// ```
     ///
     /// [`set_fps`]: #method.set_fps
-    pub fn cb_sink(&self) -> &mpsc::Sender<Box<Callback>> {
+    pub fn cb_sink(&self) -> &CbSink {
         &self.cb_sink
     }
 
+    /// Runs a callback once, after the given delay.
+    ///
+    /// The callback is run from the event loop, as soon as possible after
+    /// the delay expires. The event loop is woken up if needed.
+    ///
+    /// Since it is only run once, the callback can consume values it
+    /// captured.
+    ///
+    /// Returns a handle that can be used to cancel the timer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # extern crate cursive;
+    /// # use cursive::*;
+    /// # use std::time::Duration;
+    /// # fn main() {
+    /// let mut siv = Cursive::new();
+    ///
+    /// // Quit after 5 seconds.
+    /// siv.add_timer(Duration::from_secs(5), |s| s.quit());
+    /// # }
+    /// ```
+    pub fn add_timer<F>(&mut self, delay: Duration, cb: F) -> TimerHandle
+    where
+        F: 'static + FnOnce(&mut Cursive),
+    {
+        self.timers.add(delay, Action::Once(Box::new(cb)))
+    }
+
+    /// Runs a callback regularly, every `period`.
+    ///
+    /// The first call happens after one period. If the event loop is late,
+    /// missed calls are skipped rather than run in a burst.
+    ///
+    /// Use the returned handle to stop the interval.
+    pub fn add_interval<F>(&mut self, period: Duration, cb: F) -> TimerHandle
+    where
+        F: 'static + Fn(&mut Cursive),
+    {
+        self.timers
+            .add(period, Action::Repeat(period, Callback::from_fn(cb)))
+    }
+
+    /// Runs every timer that expired.
+    fn run_timers(&mut self) {
+        for timer in self.timers.take_expired() {
+            // A previous callback may have cancelled this one.
+            if !timer.handle.is_active() {
+                continue;
+            }
+            match timer.action {
+                Action::Once(cb) => {
+                    timer.handle.cancel();
+                    cb.call_box(self);
+                }
+                Action::Repeat(_, cb) => cb(self),
+            }
+        }
+    }
+
     /// Returns the currently used theme.
     pub fn current_theme(&self) -> &theme::Theme {
         &self.theme
@@ -139,8 +317,8 @@ impl Cursive {
     ///
     /// Regularly redraws everything, even when no input is given.
     ///
-    /// You currently need this to regularly check
-    /// for events sent using [`cb_sink`].
+    /// With backends that cannot be woken up, you need this to regularly
+    /// check for events sent using [`cb_sink`].
     ///
     /// Between 0 and 1000. Call with `fps = 0` to disable (default value).
     ///
@@ -149,6 +327,13 @@ impl Cursive {
         self.backend.set_refresh_rate(fps)
     }
 
+    /// Sets the maximum delay between two clicks of a double-click.
+    ///
+    /// Defaults to 500ms.
+    pub fn set_click_interval(&mut self, interval: Duration) {
+        self.clicks.set_interval(interval);
+    }
+
     /// Returns a reference to the currently active screen.
     pub fn root(&self) -> &views::Classic {
         &self.root
@@ -294,7 +479,7 @@ impl Cursive {
         F: Fn(&mut Cursive) + 'static,
     {
         self.global_callbacks
-            .entry(event.into())
+            .entry(event.into().normalized())
             .or_insert_with(Vec::new)
             .push(Callback::from_fn(cb));
     }
@@ -317,13 +502,19 @@ impl Cursive {
     where
         E: Into<Event>,
     {
-        let event = event.into();
+        let event = event.into().normalized();
         self.global_callbacks.remove(&event);
     }
 
     // Handles a key event when it was ignored by the current view
     fn on_event(&mut self, event: Event) {
         let cb_list = match self.global_callbacks.get(&event) {
+            None if event == Event::ModChar(Modifiers::CTRL, 'z') => {
+                // Backends read Ctrl-Z as a key instead of letting the
+                // terminal stop the process: we have to handle it.
+                self.suspend_process();
+                return;
+            }
             None => return,
             Some(cb_list) => cb_list.clone(),
         };
@@ -333,6 +524,81 @@ impl Cursive {
         }
     }
 
+    /// Suspends the user interface while running `f`.
+    ///
+    /// The terminal is given back in its original state, so `f` can run
+    /// another program using it, like a text editor or a shell.
+    /// Everything is redrawn once `f` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # extern crate cursive;
+    /// # use cursive::*;
+    /// # use std::process::Command;
+    /// # fn main() {
+    /// let mut siv = Cursive::new();
+    ///
+    /// siv.add_global_callback('e', |s| {
+    ///     let status = s.suspend_with(|| Command::new("vi").status());
+    ///     if status.is_err() {
+    ///         s.quit();
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    pub fn suspend_with<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.backend.suspend();
+        let result = f();
+        self.backend.resume();
+        self.clear();
+        result
+    }
+
+    /// Stops the process, like Ctrl-Z in a shell.
+    ///
+    /// The terminal is given back while stopped, and everything is redrawn
+    /// once the process is continued (for instance with `fg`).
+    ///
+    /// This is what happens when Ctrl-Z is pressed, unless a view or a
+    /// global callback handles it.
+    pub fn suspend_process(&mut self) {
+        self.backend.suspend();
+        self.backend.stop_process();
+        self.backend.resume();
+        self.clear();
+    }
+
+    /// Sets the title of the terminal window.
+    ///
+    /// Does nothing if the backend doesn't support it.
+    pub fn set_window_title(&mut self, title: &str) {
+        self.backend.set_title(title);
+    }
+
+    /// Rings the terminal bell.
+    pub fn bell(&mut self) {
+        self.backend.bell();
+    }
+
+    /// Briefly flashes the screen.
+    ///
+    /// Rings the bell instead if the backend cannot flash.
+    pub fn flash(&mut self) {
+        self.backend.flash();
+    }
+
+    /// Copies `text` to the system clipboard.
+    ///
+    /// Terminal backends use the OSC 52 escape sequence, which some
+    /// terminals ignore or only allow after configuration.
+    pub fn copy_to_clipboard(&mut self, text: &str) {
+        self.backend.set_clipboard(text);
+    }
+
     /// Returns the size of the screen, in characters.
     pub fn screen_size(&self) -> Vec2 {
         let (x, y) = self.backend.screen_size();
@@ -355,8 +621,9 @@ impl Cursive {
             self.last_sizes = sizes;
         }
 
-        let printer =
+        let mut printer =
             Printer::new(self.screen_size(), &self.theme, &self.backend);
+        printer.set_mouse_position(self.mouse_position);
 
         self.root.draw(&printer);
     }
@@ -396,10 +663,6 @@ impl Cursive {
     ///
     /// [`run(&mut self)`]: #method.run
     pub fn step(&mut self) {
-        while let Ok(cb) = self.cb_source.try_recv() {
-            cb(self);
-        }
-
         // Do we need to redraw everytime?
         // Probably, actually.
         // TODO: Do we need to re-layout everytime?
@@ -412,7 +675,11 @@ impl Cursive {
 
         // Wait for next event.
         // (If set_fps was called, this returns -1 now and then)
-        let event = self.backend.poll_event();
+        // Don't wait longer than the next timer.
+        let event = match self.timers.next_delay() {
+            Some(delay) => self.backend.poll_event_timeout(delay),
+            None => self.backend.poll_event(),
+        }.normalized();
         if event == Event::Exit {
             self.quit();
         }
@@ -421,6 +688,36 @@ impl Cursive {
             self.clear();
         }
 
+        // Nothing is hovered once the mouse left the window.
+        match event {
+            Event::Mouse { position, .. } if position < self.screen_size() => {
+                self.mouse_position = Some(position);
+            }
+            Event::Mouse { .. } | Event::FocusOut => {
+                self.mouse_position = None;
+            }
+            _ => (),
+        }
+
+        // A release may end a click, which is sent right after it.
+        let click = self.clicks.process(&event);
+
+        self.dispatch(event);
+        if let Some(click) = click {
+            self.dispatch(click);
+        }
+
+        // Asynchronous callbacks are processed last, so that a callback
+        // waking up `poll_event` (and maybe calling `quit`) is handled
+        // before the next wait.
+        while let Ok(cb) = self.cb_source.try_recv() {
+            cb.call_box(self);
+        }
+
+        self.run_timers();
+    }
+
+    fn dispatch(&mut self, event: Event) {
         // Event dispatch order:
         // * Root element:
         // * Global callbacks
@@ -429,8 +726,16 @@ impl Cursive {
             // If the event was ignored,
             // it is our turn to play with it.
             EventResult::Ignored => self.on_event(event),
-            EventResult::Consumed(None) => (),
-            EventResult::Consumed(Some(cb)) => cb(self),
+            EventResult::Consumed(cb) => {
+                if let Some(cb) = cb {
+                    cb(self);
+                }
+                // Focus changes concern the whole application:
+                // global callbacks always get them.
+                if event == Event::FocusIn || event == Event::FocusOut {
+                    self.on_event(event);
+                }
+            }
         }
     }
 
@@ -445,3 +750,224 @@ impl Drop for Cursive {
         self.backend.finish();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use std::thread;
+    use view::Identifiable;
+
+    #[test]
+    fn cb_sink_from_thread() {
+        let backend = Puppet::new((10, 1));
+        let screen = backend.screen();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut()
+            .add_fullscreen_layer(views::TextView::new("").with_id("text"));
+
+        let sink = siv.cb_sink().clone();
+        let message = String::from("Done");
+        thread::spawn(move || {
+            let cb = move |s: &mut Cursive| {
+                // `message` is moved out: this can only run once.
+                s.call_on_id("text", |view: &mut views::TextView| {
+                    view.set_content(message);
+                });
+                s.quit();
+            };
+            sink.send(Box::new(cb)).unwrap();
+        }).join()
+            .unwrap();
+
+        siv.step();
+        assert!(!siv.is_running());
+
+        siv.step();
+        assert_eq!(screen.borrow().line(0).trim(), "Done");
+    }
+
+    #[test]
+    fn timers() {
+        let mut siv = Cursive::with_backend(Puppet::new((10, 1)));
+        siv.root_mut()
+            .add_fullscreen_layer(views::TextView::new("").with_id("text"));
+
+        let append = |text: &'static str| {
+            move |s: &mut Cursive| {
+                s.call_on_id("text", |view: &mut views::TextView| {
+                    view.append(text);
+                });
+            }
+        };
+
+        // One-shot timers can consume what they captured.
+        let text = String::from("a");
+        let once = siv.add_timer(Duration::from_secs(0), move |s| {
+            s.call_on_id("text", |view: &mut views::TextView| {
+                view.append(text);
+            });
+        });
+        let every = siv.add_interval(Duration::from_millis(5), append("b"));
+        let never = siv.add_timer(Duration::from_secs(0), append("c"));
+        never.cancel();
+
+        let content = |s: &mut Cursive| {
+            s.call_on_id("text", |view: &mut views::TextView| {
+                String::from(view.get_content().source())
+            }).unwrap()
+        };
+
+        siv.step();
+        assert!(!once.is_active());
+        thread::sleep(Duration::from_millis(10));
+        siv.step();
+        let ticks = content(&mut siv).matches('b').count();
+        assert!(ticks >= 1);
+
+        every.cancel();
+        thread::sleep(Duration::from_millis(10));
+        siv.step();
+
+        let content = content(&mut siv);
+        assert_eq!(content.matches('a').count(), 1);
+        assert_eq!(content.matches('b').count(), ticks);
+        assert!(!content.contains('c'));
+    }
+
+    #[test]
+    fn hover() {
+        use event::MouseEvent;
+        use theme::ColorStyle;
+
+        let backend = Puppet::new((5, 2));
+        let screen = backend.screen();
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut().add_fullscreen_layer(
+            views::LinearLayout::vertical()
+                .child(views::Button::new_raw("A", |_| ()))
+                .child(views::Button::new_raw("B", |_| ())),
+        );
+
+        input.borrow_mut().push_back(Event::Mouse {
+            event: MouseEvent::Move,
+            position: Vec2::new(0, 1),
+            offset: Vec2::zero(),
+        });
+        siv.step();
+        siv.step();
+
+        let palette = &siv.current_theme().palette;
+        let screen = screen.borrow();
+        assert_eq!(screen.line(1), "B    ");
+        // Moving the mouse doesn't take the focus away.
+        assert_eq!(
+            screen.cell((0, 0)).unwrap().colors,
+            ColorStyle::highlight().resolve(palette)
+        );
+        assert_eq!(
+            screen.cell((0, 1)).unwrap().colors,
+            ColorStyle::highlight_inactive().resolve(palette)
+        );
+    }
+
+    #[test]
+    fn hover_cleared() {
+        use event::MouseEvent;
+        use theme::ColorStyle;
+
+        let backend = Puppet::new((5, 2));
+        let screen = backend.screen();
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut().add_fullscreen_layer(
+            views::LinearLayout::vertical()
+                .child(views::Button::new_raw("A", |_| ()))
+                .child(views::Button::new_raw("B", |_| ())),
+        );
+
+        let hovered = |siv: &mut Cursive, event: Event| {
+            input.borrow_mut().push_back(Event::Mouse {
+                event: MouseEvent::Move,
+                position: Vec2::new(0, 1),
+                offset: Vec2::zero(),
+            });
+            siv.step();
+            input.borrow_mut().push_back(event);
+            siv.step();
+            siv.step();
+
+            let palette = &siv.current_theme().palette;
+            screen.borrow().cell((0, 1)).unwrap().colors
+                != ColorStyle::primary().resolve(palette)
+        };
+
+        // Leaving the window, or moving out of it, ends the hover.
+        assert!(!hovered(&mut siv, Event::FocusOut));
+        let outside = Event::Mouse {
+            event: MouseEvent::Move,
+            position: Vec2::new(7, 1),
+            offset: Vec2::zero(),
+        };
+        assert!(!hovered(&mut siv, outside));
+        assert!(hovered(&mut siv, Event::Refresh));
+    }
+
+    #[test]
+    fn focus_events() {
+        let backend = Puppet::new((10, 1));
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        // This view consumes every event.
+        siv.root_mut().add_fullscreen_layer(
+            views::Canvas::new(())
+                .with_on_event(|_, _| EventResult::Consumed(None)),
+        );
+        siv.add_global_callback(Event::FocusOut, |s| s.quit());
+
+        input.borrow_mut().push_back(Event::FocusIn);
+        siv.step();
+        assert!(siv.is_running());
+
+        input.borrow_mut().push_back(Event::FocusOut);
+        siv.step();
+        assert!(!siv.is_running());
+    }
+
+    #[test]
+    fn title_and_clipboard() {
+        let backend = Puppet::new((10, 1));
+        let screen = backend.screen();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.set_window_title("Editor");
+        siv.add_global_callback('c', |s| s.copy_to_clipboard("copied"));
+        siv.on_event(Event::Char('c'));
+
+        assert_eq!(screen.borrow().title(), Some("Editor"));
+        assert_eq!(screen.borrow().clipboard(), Some("copied"));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn legacy_global_callback() {
+        use event::Modifiers;
+
+        let backend = Puppet::new((10, 1));
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.add_global_callback(Event::CtrlChar('q'), |s| s.quit());
+
+        input
+            .borrow_mut()
+            .push_back(Event::ModChar(Modifiers::CTRL, 'q'));
+        siv.step();
+        assert!(!siv.is_running());
+    }
+}
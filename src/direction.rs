@@ -58,15 +58,16 @@ impl Orientation {
     ///
     /// For an horizontal view, returns (Sum(x), Max(y)).
     /// For a vertical view, returns (Max(x),Sum(y)).
+    ///
+    /// The sum saturates at `usize::MAX`, since views given an unbounded
+    /// size may ask for all of it.
     pub fn stack<'a, T: Iterator<Item = &'a Vec2>>(&self, iter: T) -> Vec2 {
-        match *self {
-            Orientation::Horizontal => {
-                iter.fold(Vec2::zero(), |a, b| a.stack_horizontal(b))
-            }
-            Orientation::Vertical => {
-                iter.fold(Vec2::zero(), |a, b| a.stack_vertical(b))
-            }
-        }
+        iter.fold(Vec2::zero(), |a, b| {
+            let mut result = Vec2::max(a, *b);
+            *self.get_ref(&mut result) =
+                self.get(&a).saturating_add(self.get(b));
+            result
+        })
     }
 
     /// Creates a new `Vec2` with `value` in `self`'s axis.
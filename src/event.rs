@@ -14,8 +14,10 @@
 //!   table is checked.
 
 use Cursive;
-use std::ops::Deref;
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign, Deref};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 use vec::Vec2;
 
 /// Callback is a function that can be triggered by an event.
@@ -217,6 +219,127 @@ impl Key {
     }
 }
 
+/// Set of modifier keys held during a key press.
+///
+/// Sets can be combined with `|`:
+///
+/// ```rust
+/// # use cursive::event::Modifiers;
+/// let mods = Modifiers::CTRL | Modifiers::SHIFT;
+/// assert!(mods.ctrl() && mods.shift());
+/// assert!(!mods.alt());
+/// ```
+///
+/// The constants can also be used in patterns:
+///
+/// ```rust
+/// # use cursive::event::{Event, Key, Modifiers};
+/// # let event = Event::ModKey(Modifiers::CTRL, Key::Left);
+/// match event {
+///     Event::ModKey(Modifiers::CTRL, Key::Left) => (),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Modifiers {
+    bits: u8,
+}
+
+impl Modifiers {
+    /// No modifier.
+    pub const NONE: Modifiers = Modifiers { bits: 0 };
+    /// The Shift key.
+    pub const SHIFT: Modifiers = Modifiers { bits: 1 };
+    /// The Alt (or Meta) key.
+    pub const ALT: Modifiers = Modifiers { bits: 1 << 1 };
+    /// The Ctrl key.
+    pub const CTRL: Modifiers = Modifiers { bits: 1 << 2 };
+    /// The Super (or Windows, or Command) key.
+    pub const SUPER: Modifiers = Modifiers { bits: 1 << 3 };
+
+    /// The Alt and Shift keys.
+    pub const ALT_SHIFT: Modifiers = Modifiers { bits: 1 | 1 << 1 };
+    /// The Ctrl and Shift keys.
+    pub const CTRL_SHIFT: Modifiers = Modifiers { bits: 1 | 1 << 2 };
+    /// The Ctrl and Alt keys.
+    pub const CTRL_ALT: Modifiers = Modifiers { bits: 1 << 1 | 1 << 2 };
+
+    /// Returns `true` if no modifier is set.
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    /// Returns `true` if every modifier in `other` is also in `self`.
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    /// Returns `true` if the Shift key is set.
+    pub fn shift(self) -> bool {
+        self.contains(Modifiers::SHIFT)
+    }
+
+    /// Returns `true` if the Alt key is set.
+    pub fn alt(self) -> bool {
+        self.contains(Modifiers::ALT)
+    }
+
+    /// Returns `true` if the Ctrl key is set.
+    pub fn ctrl(self) -> bool {
+        self.contains(Modifiers::CTRL)
+    }
+
+    /// Returns `true` if the Super key is set.
+    pub fn super_key(self) -> bool {
+        self.contains(Modifiers::SUPER)
+    }
+
+    /// Returns `self` without the modifiers in `other`.
+    pub fn without(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            bits: self.bits & !other.bits,
+        }
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers {
+            bits: self.bits | other.bits,
+        }
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, other: Modifiers) {
+        self.bits |= other.bits;
+    }
+}
+
+impl fmt::Debug for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (Modifiers::CTRL, "Ctrl"),
+            (Modifiers::ALT, "Alt"),
+            (Modifiers::SHIFT, "Shift"),
+            (Modifiers::SUPER, "Super"),
+        ];
+        let names: Vec<&str> = names
+            .iter()
+            .filter(|&&(m, _)| self.contains(m))
+            .map(|&(_, name)| name)
+            .collect();
+
+        if names.is_empty() {
+            write!(f, "None")
+        } else {
+            write!(f, "{}", names.join("+"))
+        }
+    }
+}
+
 /// One of the buttons present on the mouse
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum MouseButton {
@@ -250,17 +373,29 @@ pub enum MouseEvent {
     WheelUp,
     /// The wheel was moved down.
     WheelDown,
+    /// The mouse was moved without any button held.
+    ///
+    /// Only sent by backends that support any-event mouse tracking.
+    Move,
+    /// A button was clicked, possibly several times in a row.
+    ///
+    /// Sent right after the `Release` ending a click, with the number of
+    /// consecutive clicks: `2` for a double-click, `3` for a triple-click...
+    ///
+    /// See [`ClickCounter`](struct.ClickCounter.html).
+    Click(MouseButton, usize),
 }
 
 impl MouseEvent {
     /// Returns the button used by this event, if any.
     ///
-    /// Returns `None` if `self` is `WheelUp` or `WheelDown`.
+    /// Returns `None` if `self` is `WheelUp`, `WheelDown` or `Move`.
     pub fn button(&self) -> Option<MouseButton> {
         match *self {
             MouseEvent::Press(btn)
             | MouseEvent::Release(btn)
-            | MouseEvent::Hold(btn) => Some(btn),
+            | MouseEvent::Hold(btn)
+            | MouseEvent::Click(btn, _) => Some(btn),
             _ => None,
         }
     }
@@ -278,36 +413,162 @@ impl MouseEvent {
     }
 }
 
+/// Detects multiple clicks from mouse events.
+///
+/// Presses of the same button, at the same position, each within a short
+/// interval of the previous one, count as a multi-click.
+///
+/// `Cursive` already runs one on every event, and sends the resulting
+/// `MouseEvent::Click` events to the views.
+#[derive(Clone, Debug)]
+pub struct ClickCounter {
+    interval: Duration,
+    /// Button, position, time and click count of the last press.
+    last_press: Option<(MouseButton, Vec2, Instant, usize)>,
+}
+
+impl Default for ClickCounter {
+    fn default() -> Self {
+        ClickCounter::new()
+    }
+}
+
+impl ClickCounter {
+    /// Creates a new counter, with an interval of 500ms.
+    pub fn new() -> Self {
+        ClickCounter {
+            interval: Duration::from_millis(500),
+            last_press: None,
+        }
+    }
+
+    /// Sets the maximum delay between two presses of a multi-click.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Returns the maximum delay between two presses of a multi-click.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Processes an event coming from the backend.
+    ///
+    /// Returns a `MouseEvent::Click` event if `event` ends a click.
+    pub fn process(&mut self, event: &Event) -> Option<Event> {
+        self.process_at(event, Instant::now())
+    }
+
+    fn process_at(&mut self, event: &Event, now: Instant) -> Option<Event> {
+        let (event, position, offset) = match *event {
+            Event::Mouse {
+                event,
+                position,
+                offset,
+            } => (event, position, offset),
+            _ => return None,
+        };
+
+        match event {
+            MouseEvent::Press(btn) => {
+                let count = match self.last_press {
+                    Some((last_btn, last_position, time, count))
+                        if last_btn == btn
+                            && last_position == position
+                            && now.duration_since(time) <= self.interval =>
+                    {
+                        count + 1
+                    }
+                    _ => 1,
+                };
+                self.last_press = Some((btn, position, now, count));
+                None
+            }
+            MouseEvent::Release(btn) => match self.last_press {
+                Some((last_btn, last_position, _, count))
+                    if last_btn == btn && last_position == position =>
+                {
+                    Some(Event::Mouse {
+                        event: MouseEvent::Click(btn, count),
+                        position,
+                        offset,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
 /// Represents an event as seen by the application.
 #[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub enum Event {
     /// Event fired when the window is resized.
     WindowResize,
 
+    /// The terminal window gained focus.
+    ///
+    /// Only sent by backends that support focus reporting, with terminals
+    /// that implement it.
+    FocusIn,
+    /// The terminal window lost focus.
+    ///
+    /// Only sent by backends that support focus reporting, with terminals
+    /// that implement it.
+    FocusOut,
+
     /// Event fired regularly when a auto-refresh is set.
     Refresh,
 
-    // TODO: have Char(modifier, char) and Key(modifier, key) enums?
     /// A character was entered (includes numbers, punctuation, ...).
     Char(char),
+    /// A character was entered with some modifiers pressed.
+    ///
+    /// The modifier set is never empty: plain characters use `Char`.
+    ///
+    /// Shift is usually already applied to the character itself
+    /// (`Char('A')`), and is only reported here by backends that can
+    /// distinguish it, alongside other modifiers.
+    ModChar(Modifiers, char),
+
+    /// A non-character key was pressed.
+    Key(Key),
+    /// A non-character key was pressed with some modifiers pressed.
+    ///
+    /// The modifier set is never empty: plain keys use `Key`.
+    ModKey(Modifiers, Key),
+
     /// A character was entered with the Ctrl key pressed.
+    #[deprecated(note = "Use `Event::ModChar(Modifiers::CTRL, c)` instead.")]
     CtrlChar(char),
     /// A character was entered with the Alt key pressed.
+    #[deprecated(note = "Use `Event::ModChar(Modifiers::ALT, c)` instead.")]
     AltChar(char),
 
-    /// A non-character key was pressed.
-    Key(Key),
     /// A non-character key was pressed with the Shift key pressed.
+    #[deprecated(note = "Use `Event::ModKey(Modifiers::SHIFT, k)` instead.")]
     Shift(Key),
     /// A non-character key was pressed with the Alt key pressed.
+    #[deprecated(note = "Use `Event::ModKey(Modifiers::ALT, k)` instead.")]
     Alt(Key),
     /// A non-character key was pressed with the Shift and Alt keys pressed.
+    #[deprecated(
+        note = "Use `Event::ModKey(Modifiers::ALT_SHIFT, k)` instead."
+    )]
     AltShift(Key),
     /// A non-character key was pressed with the Ctrl key pressed.
+    #[deprecated(note = "Use `Event::ModKey(Modifiers::CTRL, k)` instead.")]
     Ctrl(Key),
     /// A non-character key was pressed with the Ctrl and Shift keys pressed.
+    #[deprecated(
+        note = "Use `Event::ModKey(Modifiers::CTRL_SHIFT, k)` instead."
+    )]
     CtrlShift(Key),
     /// A non-character key was pressed with the Ctrl and Alt keys pressed.
+    #[deprecated(
+        note = "Use `Event::ModKey(Modifiers::CTRL_ALT, k)` instead."
+    )]
     CtrlAlt(Key),
 
     /// A mouse event was sent.
@@ -320,6 +581,12 @@ pub enum Event {
         event: MouseEvent,
     },
 
+    /// Some text was pasted.
+    ///
+    /// Backends supporting bracketed paste send the whole text at once,
+    /// instead of one event per character. Newlines are always `\n`.
+    Paste(String),
+
     // TODO: use a backend-dependent type for the unknown values?
     /// An unknown event was received.
     Unknown(Vec<u8>),
@@ -334,6 +601,93 @@ pub enum Event {
 }
 
 impl Event {
+    /// Returns a key event with the given modifiers.
+    ///
+    /// Returns `Event::Key` if `modifiers` is empty.
+    pub fn key_with(modifiers: Modifiers, key: Key) -> Self {
+        if modifiers.is_empty() {
+            Event::Key(key)
+        } else {
+            Event::ModKey(modifiers, key)
+        }
+    }
+
+    /// Returns a character event with the given modifiers.
+    ///
+    /// Returns `Event::Char` if `modifiers` is empty.
+    pub fn char_with(modifiers: Modifiers, c: char) -> Self {
+        if modifiers.is_empty() {
+            Event::Char(c)
+        } else {
+            Event::ModChar(modifiers, c)
+        }
+    }
+
+    /// Returns the modifiers held during this event.
+    ///
+    /// Returns an empty set for events other than keys or characters.
+    pub fn modifiers(&self) -> Modifiers {
+        match self.normalized() {
+            Event::ModChar(modifiers, _) | Event::ModKey(modifiers, _) => {
+                modifiers
+            }
+            _ => Modifiers::NONE,
+        }
+    }
+
+    /// Converts deprecated modifier variants to `ModChar` or `ModKey`.
+    ///
+    /// Other events are returned unchanged. Events coming from the backend
+    /// are always normalized.
+    #[allow(deprecated)]
+    pub fn normalized(&self) -> Self {
+        match *self {
+            Event::CtrlChar(c) => Event::ModChar(Modifiers::CTRL, c),
+            Event::AltChar(c) => Event::ModChar(Modifiers::ALT, c),
+            Event::Shift(k) => Event::ModKey(Modifiers::SHIFT, k),
+            Event::Alt(k) => Event::ModKey(Modifiers::ALT, k),
+            Event::AltShift(k) => Event::ModKey(Modifiers::ALT_SHIFT, k),
+            Event::Ctrl(k) => Event::ModKey(Modifiers::CTRL, k),
+            Event::CtrlShift(k) => Event::ModKey(Modifiers::CTRL_SHIFT, k),
+            Event::CtrlAlt(k) => Event::ModKey(Modifiers::CTRL_ALT, k),
+            Event::ModChar(modifiers, c) => Event::char_with(modifiers, c),
+            Event::ModKey(modifiers, k) => Event::key_with(modifiers, k),
+            ref other => other.clone(),
+        }
+    }
+
+    /// Converts this event to a deprecated modifier variant, if possible.
+    ///
+    /// This can help with code still matching on these variants:
+    ///
+    /// ```rust
+    /// # #![allow(deprecated)]
+    /// # use cursive::event::{Event, Modifiers};
+    /// let event = Event::ModChar(Modifiers::CTRL, 'f');
+    /// match event.legacy() {
+    ///     Event::CtrlChar('f') => (),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    ///
+    /// Events that cannot be represented by a deprecated variant (for
+    /// instance with the Super key, or Ctrl+Alt+Shift) are returned
+    /// normalized.
+    #[allow(deprecated)]
+    pub fn legacy(&self) -> Self {
+        match self.normalized() {
+            Event::ModChar(Modifiers::CTRL, c) => Event::CtrlChar(c),
+            Event::ModChar(Modifiers::ALT, c) => Event::AltChar(c),
+            Event::ModKey(Modifiers::SHIFT, k) => Event::Shift(k),
+            Event::ModKey(Modifiers::ALT, k) => Event::Alt(k),
+            Event::ModKey(Modifiers::ALT_SHIFT, k) => Event::AltShift(k),
+            Event::ModKey(Modifiers::CTRL, k) => Event::Ctrl(k),
+            Event::ModKey(Modifiers::CTRL_SHIFT, k) => Event::CtrlShift(k),
+            Event::ModKey(Modifiers::CTRL_ALT, k) => Event::CtrlAlt(k),
+            other => other,
+        }
+    }
+
     /// Returns the position of the mouse, if `self` is a mouse event.
     pub fn mouse_position(&self) -> Option<Vec2> {
         if let Event::Mouse { position, .. } = *self {
@@ -381,3 +735,101 @@ impl From<Key> for Event {
         Event::Key(k)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn legacy_round_trip() {
+        let legacy = vec![
+            Event::CtrlChar('a'),
+            Event::AltChar('b'),
+            Event::Shift(Key::Tab),
+            Event::Alt(Key::Left),
+            Event::AltShift(Key::Right),
+            Event::Ctrl(Key::Home),
+            Event::CtrlShift(Key::End),
+            Event::CtrlAlt(Key::Del),
+        ];
+
+        for event in legacy {
+            let normalized = event.normalized();
+            assert!(!normalized.modifiers().is_empty());
+            assert_eq!(normalized.legacy(), event);
+        }
+    }
+
+    #[test]
+    fn click_count() {
+        use self::MouseButton::{Left, Right};
+
+        let mouse = |event, x| Event::Mouse {
+            event,
+            position: Vec2::new(x, 0),
+            offset: Vec2::zero(),
+        };
+        let press = |btn, x| mouse(MouseEvent::Press(btn), x);
+        let release = |btn, x| mouse(MouseEvent::Release(btn), x);
+        let click = |btn, x, count| mouse(MouseEvent::Click(btn, count), x);
+
+        let mut counter = ClickCounter::new();
+        let start = Instant::now();
+        let mut clicks = |events: &[(Event, u64)]| {
+            events
+                .iter()
+                .filter_map(|&(ref event, ms)| {
+                    let now = start + Duration::from_millis(ms);
+                    counter.process_at(event, now)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            clicks(&[
+                (press(Left, 1), 0),
+                (release(Left, 1), 50),
+                (press(Left, 1), 300),
+                (release(Left, 1), 350),
+                (press(Left, 1), 600),
+                (release(Left, 1), 650),
+            ]),
+            vec![click(Left, 1, 1), click(Left, 1, 2), click(Left, 1, 3)]
+        );
+
+        // Too slow: this starts a new series.
+        assert_eq!(
+            clicks(&[(press(Left, 1), 2000), (release(Left, 1), 2050)]),
+            vec![click(Left, 1, 1)]
+        );
+
+        // Released somewhere else, or pressed with another button.
+        assert_eq!(
+            clicks(&[
+                (press(Left, 2), 2100),
+                (release(Left, 1), 2150),
+                (press(Right, 1), 2200),
+                (release(Right, 1), 2250),
+            ]),
+            vec![click(Right, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn new_combinations() {
+        let mods = Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT;
+        let event = Event::ModChar(mods, 'x');
+
+        assert_eq!(event.normalized(), event);
+        assert_eq!(event.legacy(), event);
+        assert_eq!(event.modifiers(), mods);
+        assert_eq!(format!("{:?}", mods), "Ctrl+Alt+Shift");
+
+        let event = Event::key_with(Modifiers::NONE, Key::Up);
+        assert_eq!(event, Event::Key(Key::Up));
+
+        let event = Event::ModKey(Modifiers::NONE, Key::Up);
+        assert_eq!(event.normalized(), Event::Key(Key::Up));
+    }
+}
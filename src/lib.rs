@@ -117,11 +117,13 @@ mod with;
 
 mod div;
 mod utf8;
+mod timer;
 
 #[doc(hidden)]
 pub mod backend;
 
-pub use cursive::{Cursive, ScreenId};
+pub use cursive::{CbFunc, CbSink, Cursive, ScreenId};
 pub use printer::Printer;
+pub use timer::TimerHandle;
 pub use with::With;
 pub use xy::XY;
@@ -1,20 +1,26 @@
 //! Makes drawing on ncurses windows easier.
 
-use backend::Backend;
+use backend::{Backend, Capabilities, CursorShape};
 use enumset::EnumSet;
 use std::cell::Cell;
 use std::cmp::min;
 use std::rc::Rc;
 use theme::{BorderStyle, ColorStyle, Effect, PaletteColor, Style, Theme};
 use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 use utils::lines::simple::prefix;
 use vec::Vec2;
 
 /// Convenient interface to draw on a subset of the screen.
 pub struct Printer<'a> {
     /// Offset into the window this printer should start drawing at.
+    ///
+    /// If the content is scrolled, this is where the visible part of the
+    /// content starts.
     pub offset: Vec2,
     /// Size of the area we are allowed to draw on.
+    ///
+    /// If the content is scrolled, only a part of it is actually visible.
     pub size: Vec2,
     /// Whether the view to draw is currently focused or not.
     pub focused: bool,
@@ -25,6 +31,12 @@ pub struct Printer<'a> {
     new: Rc<Cell<bool>>,
     /// Backend used to actually draw things
     backend: &'a Box<Backend>,
+    /// Last known position of the mouse on the screen.
+    mouse: Option<Vec2>,
+    /// Position, in the content, of the first visible cell.
+    content_offset: Vec2,
+    /// Size of the visible part of the content.
+    output_size: Vec2,
 }
 
 impl<'a> Printer<'a> {
@@ -35,16 +47,81 @@ impl<'a> Printer<'a> {
     pub fn new<T: Into<Vec2>>(
         size: T, theme: &'a Theme, backend: &'a Box<Backend>
     ) -> Self {
+        let size = size.into();
         Printer {
             offset: Vec2::zero(),
-            size: size.into(),
+            size,
             focused: true,
             theme: theme,
             new: Rc::new(Cell::new(true)),
             backend: backend,
+            mouse: None,
+            content_offset: Vec2::zero(),
+            output_size: size,
         }
     }
 
+    /// Sets the position of the mouse on the screen.
+    ///
+    /// `None` means no view should be drawn as hovered.
+    pub(crate) fn set_mouse_position(&mut self, position: Option<Vec2>) {
+        self.mouse = position;
+    }
+
+    /// Returns the position of the mouse, relative to this printer.
+    ///
+    /// Returns `None` if the mouse is outside of the area of this printer,
+    /// or if its position is unknown.
+    ///
+    /// Only backends that report mouse moves keep this position up to
+    /// date; others only update it when a button is used.
+    pub fn mouse_position(&self) -> Option<Vec2> {
+        let position = self.mouse?.checked_sub(self.offset)?;
+        let position = position + self.content_offset;
+        if self.is_visible(position) {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    // Returns `true` if the given cell is in the visible part of the
+    // content.
+    fn is_visible(&self, pos: Vec2) -> bool {
+        pos < self.size
+            && pos.fits(self.content_offset)
+            && pos < self.content_offset + self.output_size
+    }
+
+    // Returns the end of the visible part of the content.
+    fn visible_end(&self) -> Vec2 {
+        Vec2::min(self.size, self.content_offset + self.output_size)
+    }
+
+    /// Asks for the terminal cursor to be shown at the given position.
+    ///
+    /// The cursor is placed once everything is drawn, and is hidden if no
+    /// view asks for it during a frame. If several views ask for it, the
+    /// last one wins. Positions outside of this printer are ignored.
+    ///
+    /// Check [`capabilities`] first: some backends cannot show a cursor.
+    ///
+    /// [`capabilities`]: #method.capabilities
+    pub fn set_cursor<S: Into<Vec2>>(&self, pos: S, shape: CursorShape) {
+        let pos = pos.into();
+        if self.is_visible(pos) {
+            let pos = self.offset + pos - self.content_offset;
+            self.backend.show_cursor((pos.x, pos.y), shape);
+        }
+    }
+
+    /// Returns `true` if the mouse is in the area of this printer.
+    ///
+    /// Views can use this to draw a hover highlight.
+    pub fn is_hovered(&self) -> bool {
+        self.mouse_position().is_some()
+    }
+
     /// Clear the screen.
     ///
     /// It will discard anything drawn before.
@@ -60,6 +137,37 @@ impl<'a> Printer<'a> {
         self.new.get()
     }
 
+    /// Returns the features supported by the backend.
+    ///
+    /// Views can use this to adapt how they are drawn.
+    pub fn capabilities(&self) -> Capabilities {
+        self.backend.capabilities()
+    }
+
+    /// Returns `unicode`, or `ascii` if the backend cannot show
+    /// box-drawing characters.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use cursive::Printer;
+    /// # use cursive::theme;
+    /// # use cursive::backend::{self, Backend};
+    /// # let b: Box<Backend> = backend::Concrete::init().unwrap();
+    /// # let t = theme::load_default();
+    /// # let printer = Printer::new((6,4), &t, &b);
+    /// printer.print((0, 0), printer.box_drawing("├─┤", "+-+"));
+    /// ```
+    pub fn box_drawing<'b>(
+        &self, unicode: &'b str, ascii: &'b str
+    ) -> &'b str {
+        if self.capabilities().box_drawing {
+            unicode
+        } else {
+            ascii
+        }
+    }
+
     // TODO: use &mut self? We don't *need* it, but it may make sense.
     // We don't want people to start calling prints in parallel?
     /// Prints some text at the given position relative to the window.
@@ -67,17 +175,45 @@ impl<'a> Printer<'a> {
         self.new.set(false);
 
         let p = pos.into();
-        if p.y >= self.size.y || p.x >= self.size.x {
+        let end = self.visible_end();
+        if p.y < self.content_offset.y || p.y >= end.y || p.x >= end.x {
             return;
         }
+
+        // Skip the part of the text left of the visible area.
+        // A wide character cut in half is skipped entirely.
+        let mut x = p.x;
+        let mut start = 0;
+        for grapheme in text.graphemes(true) {
+            if x >= self.content_offset.x {
+                break;
+            }
+            x += grapheme.width();
+            start += grapheme.len();
+        }
+        if x < self.content_offset.x {
+            // The whole text is left of the visible area.
+            return;
+        }
+        if x >= end.x {
+            return;
+        }
+        if start > 0 && x > self.content_offset.x {
+            // Blank the visible half of the skipped character.
+            let p = self.offset + (0, p.y) - self.content_offset.keep_y();
+            let blank = " ".repeat(x - self.content_offset.x);
+            self.backend.print_at((p.x, p.y), &blank);
+        }
+
         // Do we have enough room for the entire line?
-        let room = self.size.x - p.x;
+        let room = end.x - x;
         // We want the number of CHARACTERS, not bytes.
         // (Actually we want the "width" of the string, see unicode-width)
+        let text = &text[start..];
         let prefix_len = prefix(text.graphemes(true), room, "").length;
         let text = &text[..prefix_len];
 
-        let p = p + self.offset;
+        let p = self.offset + (x, p.y) - self.content_offset;
         self.backend.print_at((p.x, p.y), text);
     }
 
@@ -86,12 +222,15 @@ impl<'a> Printer<'a> {
         self.new.set(false);
 
         let p = start.into();
-        if p.y > self.size.y || p.x > self.size.x {
+        let end = self.visible_end();
+        if p.x < self.content_offset.x || p.x >= end.x {
             return;
         }
-        let len = min(len, self.size.y - p.y);
+        // Only keep the visible part of the line.
+        let y = p.y.max(self.content_offset.y);
+        let len = min(p.y + len, end.y).saturating_sub(y);
 
-        let p = p + self.offset;
+        let p = self.offset + (p.x, y) - self.content_offset;
         for y in 0..len {
             self.backend.print_at((p.x, (p.y + y)), c);
         }
@@ -102,13 +241,19 @@ impl<'a> Printer<'a> {
         self.new.set(false);
 
         let p = start.into();
-        if p.y > self.size.y || p.x > self.size.x {
+        let end = self.visible_end();
+        if p.y < self.content_offset.y || p.y >= end.y {
+            return;
+        }
+        // Only keep the visible part of the line.
+        let x = p.x.max(self.content_offset.x);
+        let len = min(p.x + len, end.x).saturating_sub(x);
+        if len == 0 {
             return;
         }
-        let len = min(len, self.size.x - p.x);
         let text: String = ::std::iter::repeat(c).take(len).collect();
 
-        let p = p + self.offset;
+        let p = self.offset + (x, p.y) - self.content_offset;
         self.backend.print_at((p.x, p.y), &text);
     }
 
@@ -121,7 +266,7 @@ impl<'a> Printer<'a> {
     /// # use cursive::Printer;
     /// # use cursive::theme;
     /// # use cursive::backend::{self, Backend};
-    /// # let b: Box<Backend> = backend::Concrete::init();
+    /// # let b: Box<Backend> = backend::Concrete::init().unwrap();
     /// # let t = theme::load_default();
     /// # let printer = Printer::new((6,4), &t, &b);
     /// printer.with_color(theme::ColorStyle::highlight(), |printer| {
@@ -199,7 +344,7 @@ impl<'a> Printer<'a> {
     /// # use cursive::Printer;
     /// # use cursive::theme;
     /// # use cursive::backend::{self, Backend};
-    /// # let b: Box<Backend> = backend::Concrete::init();
+    /// # let b: Box<Backend> = backend::Concrete::init().unwrap();
     /// # let t = theme::load_default();
     /// # let printer = Printer::new((6,4), &t, &b);
     /// printer.print_box((0,0), (6,4), false);
@@ -217,18 +362,21 @@ impl<'a> Printer<'a> {
         }
         let size = size - (1, 1);
 
+        let hline = self.box_drawing("─", "-");
+        let vline = self.box_drawing("│", "|");
+
         self.with_high_border(invert, |s| {
-            s.print(start, "┌");
-            s.print(start + size.keep_y(), "└");
-            s.print_hline(start + (1, 0), size.x - 1, "─");
-            s.print_vline(start + (0, 1), size.y - 1, "│");
+            s.print(start, s.box_drawing("┌", "+"));
+            s.print(start + size.keep_y(), s.box_drawing("└", "+"));
+            s.print_hline(start + (1, 0), size.x - 1, hline);
+            s.print_vline(start + (0, 1), size.y - 1, vline);
         });
 
         self.with_low_border(invert, |s| {
-            s.print(start + size.keep_x(), "┐");
-            s.print(start + size, "┘");
-            s.print_hline(start + (1, 0) + size.keep_y(), size.x - 1, "─");
-            s.print_vline(start + (0, 1) + size.keep_x(), size.y - 1, "│");
+            s.print(start + size.keep_x(), s.box_drawing("┐", "+"));
+            s.print(start + size, s.box_drawing("┘", "+"));
+            s.print_hline(start + (1, 0) + size.keep_y(), size.x - 1, hline);
+            s.print_vline(start + (0, 1) + size.keep_x(), size.y - 1, vline);
         });
     }
 
@@ -295,9 +443,13 @@ impl<'a> Printer<'a> {
     /// Prints a horizontal delimiter with side border `├` and `┤`.
     pub fn print_hdelim<T: Into<Vec2>>(&self, start: T, len: usize) {
         let start = start.into();
-        self.print(start, "├");
-        self.print_hline(start + (1, 0), len.saturating_sub(2), "─");
-        self.print(start + (len.saturating_sub(1), 0), "┤");
+        let hline = self.box_drawing("─", "-");
+        self.print(start, self.box_drawing("├", "+"));
+        self.print_hline(start + (1, 0), len.saturating_sub(2), hline);
+        self.print(
+            start + (len.saturating_sub(1), 0),
+            self.box_drawing("┤", "+"),
+        );
     }
 
     /// Returns a printer on a subset of this one's area.
@@ -311,14 +463,60 @@ impl<'a> Printer<'a> {
         } else {
             Vec2::min(self.size - offset, size)
         };
+
+        // Part of the new area which is visible, in our coordinates.
+        let start = Vec2::max(offset, self.content_offset);
+        let end = Vec2::min(
+            offset + available,
+            self.content_offset + self.output_size,
+        );
+
         Printer {
-            offset: self.offset + offset,
+            offset: self.offset + start - self.content_offset,
             // We can't be larger than what remains
             size: available,
             focused: self.focused && focused,
             theme: self.theme,
             backend: self.backend,
             new: Rc::clone(&self.new),
+            mouse: self.mouse,
+            content_offset: start - offset,
+            output_size: end.saturating_sub(start),
+        }
+    }
+
+    /// Returns a printer for content of the given size, scrolled by
+    /// `offset`.
+    ///
+    /// The content can be larger than this printer: only the part starting
+    /// at `offset` and fitting in the current area is actually drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use cursive::Printer;
+    /// # use cursive::theme;
+    /// # use cursive::backend::{self, Backend};
+    /// # let b: Box<Backend> = backend::Concrete::init().unwrap();
+    /// # let t = theme::load_default();
+    /// # let printer = Printer::new((6,2), &t, &b);
+    /// // Only "Line 2" will be visible, on the first row.
+    /// let printer = printer.scrolled((0, 1), (6, 3));
+    /// printer.print((0, 1), "Line 2");
+    /// ```
+    pub fn scrolled<S: Into<Vec2>, T: Into<Vec2>>(
+        &self, offset: S, size: T
+    ) -> Printer {
+        Printer {
+            offset: self.offset,
+            size: size.into(),
+            focused: self.focused,
+            theme: self.theme,
+            backend: self.backend,
+            new: Rc::clone(&self.new),
+            mouse: self.mouse,
+            content_offset: self.content_offset + offset.into(),
+            output_size: self.output_size,
         }
     }
 
@@ -327,3 +525,30 @@ impl<'a> Printer<'a> {
         self.sub_printer(offset, self.size, focused)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use theme;
+
+    #[test]
+    fn print_left_of_visible_area() {
+        let puppet = Puppet::new((12, 2));
+        let screen = puppet.screen();
+        let backend: Box<Backend> = puppet;
+        let theme = theme::load_default();
+
+        {
+            let printer = Printer::new((12, 2), &theme, &backend);
+            let printer = printer.scrolled((10, 0), (40, 2));
+            // This line ends left of the visible area.
+            printer.print((0, 0), "short");
+            printer.print((0, 1), "a much much much longer line");
+        }
+
+        let screen = screen.borrow();
+        assert_eq!(screen.line(0), "            ");
+        assert_eq!(screen.line(1), "h much longe");
+    }
+}
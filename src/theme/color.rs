@@ -114,6 +114,64 @@ impl Color {
         }
     }
 
+    /// Returns the ID of the closest color in the 256 colors list.
+    ///
+    /// This is the reverse of [`from_256colors`].
+    /// Returns `None` for `Color::TerminalDefault`.
+    ///
+    /// [`from_256colors`]: #method.from_256colors
+    pub fn to_256colors(self) -> Option<u8> {
+        Some(match self {
+            Color::TerminalDefault => return None,
+            Color::Dark(base) => base as u8,
+            Color::Light(base) => 8 + base as u8,
+            Color::RgbLowRes(r, g, b) => 16 + 36 * r + 6 * g + b,
+            Color::Rgb(r, g, b) => rgb_to_256(r, g, b),
+        })
+    }
+
+    /// Returns the closest color that can be shown with the given depth.
+    ///
+    /// * With `TrueColor`, the color is left unchanged.
+    /// * With `Colors256`, `Rgb` becomes `RgbLowRes`, or one of the
+    ///   grayscale colors from the 256 colors list.
+    /// * With `Colors16`, every color becomes a `Dark` or `Light` base color.
+    /// * With `Colors8`, every color becomes a `Dark` base color.
+    ///
+    /// `TerminalDefault` is never changed.
+    pub fn downgrade(self, depth: ColorDepth) -> Self {
+        match (depth, self) {
+            (ColorDepth::TrueColor, color)
+            | (_, color @ Color::TerminalDefault)
+            | (_, color @ Color::Dark(_)) => color,
+            (ColorDepth::Colors256, Color::Rgb(r, g, b)) => {
+                Color::from_256colors(rgb_to_256(r, g, b))
+            }
+            (ColorDepth::Colors256, color) => color,
+            (ColorDepth::Colors16, color @ Color::Light(_)) => color,
+            (ColorDepth::Colors8, Color::Light(base)) => Color::Dark(base),
+            (ColorDepth::Colors16, color) => nearest_base(color.rgb(), 16),
+            (ColorDepth::Colors8, color) => nearest_base(color.rgb(), 8),
+        }
+    }
+
+    /// Returns the usual RGB value for this color.
+    ///
+    /// Base colors use the default xterm palette.
+    fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::TerminalDefault => (0, 0, 0),
+            Color::Dark(base) => BASE_RGB[base as usize],
+            Color::Light(base) => BASE_RGB[8 + base as usize],
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::RgbLowRes(r, g, b) => (
+                CUBE_LEVELS[r as usize],
+                CUBE_LEVELS[g as usize],
+                CUBE_LEVELS[b as usize],
+            ),
+        }
+    }
+
     pub(crate) fn parse(value: &str) -> Option<Self> {
         Some(match value {
             "black" => Color::Dark(BaseColor::Black),
@@ -169,6 +227,108 @@ impl Color {
     }
 }
 
+/// Number of colors a terminal can show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorDepth {
+    /// Only the 8 `Dark` base colors.
+    Colors8,
+    /// The 8 `Dark` and the 8 `Light` base colors.
+    Colors16,
+    /// The 256 colors list: base colors, `RgbLowRes` and grayscale.
+    Colors256,
+    /// Any 24-bit `Rgb` color.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Returns the depth of a terminal supporting `n` colors.
+    ///
+    /// This is the `colors` capability from terminfo.
+    pub fn from_colors(n: u32) -> Self {
+        if n >= 1 << 24 {
+            ColorDepth::TrueColor
+        } else if n >= 256 {
+            ColorDepth::Colors256
+        } else if n >= 16 {
+            ColorDepth::Colors16
+        } else {
+            ColorDepth::Colors8
+        }
+    }
+}
+
+/// Values of each channel for the 216 `RgbLowRes` colors.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Default xterm values for the 16 base colors.
+const BASE_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let dr = i32::from(r1) - i32::from(r2);
+    let dg = i32::from(g1) - i32::from(g2);
+    let db = i32::from(b1) - i32::from(b2);
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+/// Returns the index of the closest `CUBE_LEVELS` value.
+fn cube_index(value: u8) -> u8 {
+    if value < 48 {
+        0
+    } else if value < 115 {
+        1
+    } else {
+        (value - 35) / 40
+    }
+}
+
+/// Returns the ID of the closest color among colors 16 to 255.
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+
+    // Grayscale colors are 8 + 10 * n, for n in 0..24.
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let n = (average.max(8) - 8 + 5) / 10;
+    let n = n.min(23) as u8;
+    let value = 8 + 10 * n;
+    let gray = (value, value, value);
+
+    if distance(gray, (r, g, b)) < distance(cube, (r, g, b)) {
+        232 + n
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// Returns the closest among the first `count` base colors.
+fn nearest_base(rgb: (u8, u8, u8), count: usize) -> Color {
+    let i = (0..count)
+        .min_by_key(|&i| distance(BASE_RGB[i], rgb))
+        .unwrap_or(0);
+    Color::from_256colors(i as u8)
+}
+
 /// Loads a hexadecimal code
 fn load_hex(s: &str) -> u16 {
     let mut sum = 0;
@@ -198,4 +358,60 @@ mod tests {
             Color::from_256colors(i as u8);
         }
     }
+
+    #[test]
+    fn test_256_colors_round_trip() {
+        use super::Color;
+
+        for i in 0..256u16 {
+            let color = Color::from_256colors(i as u8);
+            assert_eq!(color.to_256colors(), Some(i as u8));
+        }
+    }
+
+    #[test]
+    fn test_downgrade() {
+        use super::{BaseColor, Color, ColorDepth};
+
+        let orange = Color::Rgb(255, 135, 0);
+        assert_eq!(orange.downgrade(ColorDepth::TrueColor), orange);
+        assert_eq!(
+            orange.downgrade(ColorDepth::Colors256),
+            Color::RgbLowRes(5, 2, 0)
+        );
+        assert_eq!(
+            orange.downgrade(ColorDepth::Colors16),
+            Color::Dark(BaseColor::Yellow)
+        );
+        assert_eq!(
+            Color::Rgb(255, 40, 40).downgrade(ColorDepth::Colors16),
+            Color::Light(BaseColor::Red)
+        );
+        assert_eq!(
+            Color::Rgb(255, 40, 40).downgrade(ColorDepth::Colors8),
+            Color::Dark(BaseColor::Red)
+        );
+
+        let gray = Color::Rgb(108, 108, 108);
+        assert_eq!(gray.downgrade(ColorDepth::Colors256), gray);
+        assert_eq!(
+            Color::Rgb(109, 107, 108).downgrade(ColorDepth::Colors256),
+            gray
+        );
+
+        let light = Color::Light(BaseColor::Blue);
+        assert_eq!(light.downgrade(ColorDepth::Colors16), light);
+        assert_eq!(
+            light.downgrade(ColorDepth::Colors8),
+            Color::Dark(BaseColor::Blue)
+        );
+        assert_eq!(
+            Color::RgbLowRes(5, 5, 5).downgrade(ColorDepth::Colors8),
+            Color::Dark(BaseColor::White)
+        );
+        assert_eq!(
+            Color::TerminalDefault.downgrade(ColorDepth::Colors8),
+            Color::TerminalDefault
+        );
+    }
 }
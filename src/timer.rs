@@ -0,0 +1,162 @@
+//! Callbacks scheduled on the event loop.
+
+use Cursive;
+use event::Callback;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Handle to a timer registered with [`Cursive::add_timer`] or
+/// [`Cursive::add_interval`].
+///
+/// It can be used to cancel the timer, from a callback or elsewhere.
+///
+/// [`Cursive::add_timer`]: struct.Cursive.html#method.add_timer
+/// [`Cursive::add_interval`]: struct.Cursive.html#method.add_interval
+#[derive(Clone, Debug)]
+pub struct TimerHandle {
+    active: Rc<Cell<bool>>,
+}
+
+impl TimerHandle {
+    fn new() -> Self {
+        TimerHandle {
+            active: Rc::new(Cell::new(true)),
+        }
+    }
+
+    /// Cancels the timer.
+    ///
+    /// Its callback will not be run anymore.
+    /// Does nothing if the timer already fired or was already cancelled.
+    pub fn cancel(&self) {
+        self.active.set(false);
+    }
+
+    /// Returns `true` if the timer may still fire.
+    ///
+    /// Returns `false` once it is cancelled, or once a non-repeating timer
+    /// has fired.
+    pub fn is_active(&self) -> bool {
+        self.active.get()
+    }
+}
+
+/// Callback run by a one-shot timer.
+///
+/// Every `FnOnce(&mut Cursive)` automatically implements this.
+///
+/// Like `CbFunc`, this is a workaround for `Box<FnOnce()>`, but without
+/// requiring `Send`: timers never leave the event loop thread.
+pub trait TimerFunc {
+    /// Calls the function.
+    fn call_box(self: Box<Self>, siv: &mut Cursive);
+}
+
+impl<F: FnOnce(&mut Cursive)> TimerFunc for F {
+    fn call_box(self: Box<Self>, siv: &mut Cursive) {
+        (*self)(siv)
+    }
+}
+
+/// What a timer does when it fires.
+pub enum Action {
+    /// Runs a callback once.
+    Once(Box<TimerFunc>),
+    /// Runs a callback every given period.
+    Repeat(Duration, Callback),
+}
+
+/// A callback waiting to be run.
+struct Timer {
+    deadline: Instant,
+    action: Action,
+    handle: TimerHandle,
+}
+
+/// Collection of timers.
+#[derive(Default)]
+pub struct Timers {
+    timers: Vec<Timer>,
+}
+
+impl Timers {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Timers { timers: Vec::new() }
+    }
+
+    /// Registers a new timer, firing first after `delay`.
+    pub fn add(&mut self, delay: Duration, action: Action) -> TimerHandle {
+        let handle = TimerHandle::new();
+        self.timers.push(Timer {
+            deadline: Instant::now() + delay,
+            action,
+            handle: handle.clone(),
+        });
+        handle
+    }
+
+    /// Returns the time left until the next timer fires, if any.
+    pub fn next_delay(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.timers
+            .iter()
+            .filter(|timer| timer.handle.is_active())
+            .map(|timer| {
+                if timer.deadline > now {
+                    timer.deadline - now
+                } else {
+                    Duration::from_secs(0)
+                }
+            })
+            .min()
+    }
+
+    /// Removes every expired timer, and returns them.
+    ///
+    /// Repeating timers are re-scheduled instead of being removed.
+    pub fn take_expired(&mut self) -> Vec<Expired> {
+        let now = Instant::now();
+        let mut expired = Vec::new();
+
+        let timers = ::std::mem::replace(&mut self.timers, Vec::new());
+        for mut timer in timers {
+            if !timer.handle.is_active() {
+                continue;
+            }
+            if timer.deadline > now {
+                self.timers.push(timer);
+                continue;
+            }
+
+            let handle = timer.handle.clone();
+            let action = match timer.action {
+                Action::Once(cb) => Action::Once(cb),
+                Action::Repeat(period, ref cb) => {
+                    timer.deadline += period;
+                    // Don't try to catch up if we're late.
+                    if timer.deadline <= now {
+                        timer.deadline = now + period;
+                    }
+                    let action = Action::Repeat(period, cb.clone());
+                    self.timers.push(timer);
+                    action
+                }
+            };
+            expired.push(Expired { action, handle });
+        }
+
+        expired
+    }
+}
+
+/// A timer that should fire now.
+pub struct Expired {
+    /// Callback to run.
+    ///
+    /// Repeating timers stay registered with a copy of it.
+    pub action: Action,
+    /// Handle to the timer.
+    pub handle: TimerHandle,
+}
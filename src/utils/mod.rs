@@ -4,5 +4,6 @@ mod reader;
 pub mod span;
 pub mod lines;
 pub mod markup;
+pub mod snapshot;
 
 pub use self::reader::ProgressReader;
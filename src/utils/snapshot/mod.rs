@@ -0,0 +1,284 @@
+//! Render views into text snapshots, and compare them to fixtures.
+//!
+//! A [`Snapshot`] is obtained by drawing a view with the default theme on
+//! a headless backend. It can then be compared to a fixture file, failing
+//! with a line diff if the rendered output changed.
+//!
+//! Each row of the screen is written between `|` characters, so trailing
+//! spaces are not lost. When annotations are enabled, each row is followed
+//! by a line of style identifiers, and a legend maps each identifier to its
+//! colors and effects.
+//!
+//! Setting the `CURSIVE_UPDATE_SNAPSHOTS` environment variable makes
+//! [`Snapshot::assert_matches`] overwrite fixtures instead of comparing them.
+//!
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`Snapshot::assert_matches`]: struct.Snapshot.html#method.assert_matches
+
+use Printer;
+use backend::Backend;
+use backend::puppet::{ObservedCell, ObservedScreen, Puppet};
+use enumset::EnumSet;
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use theme::{ColorPair, ColorStyle, Effect, Theme};
+use vec::Vec2;
+use view::View;
+
+/// Characters used to identify styles in annotated snapshots.
+const STYLE_IDS: &str =
+    "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Text rendering of a view.
+pub struct Snapshot {
+    screen: ObservedScreen,
+    annotated: bool,
+}
+
+impl Snapshot {
+    /// Renders the given view with the default theme.
+    ///
+    /// The view is laid out with the given size, then drawn on a fresh
+    /// screen of the same size, with the primary color style.
+    pub fn render<V, S>(view: &mut V, size: S) -> Self
+    where
+        V: View + ?Sized,
+        S: Into<Vec2>,
+    {
+        Snapshot::render_with_theme(view, size, &Theme::default())
+    }
+
+    /// Renders the given view with a custom theme.
+    pub fn render_with_theme<V, S>(
+        view: &mut V, size: S, theme: &Theme
+    ) -> Self
+    where
+        V: View + ?Sized,
+        S: Into<Vec2>,
+    {
+        let size = size.into();
+
+        let puppet = Puppet::new(size);
+        let screen = puppet.screen();
+        let backend: Box<Backend> = puppet;
+
+        view.layout(size);
+        {
+            // Draw the view as a `StackView` layer would be drawn.
+            let printer = Printer::new(size, theme, &backend);
+            printer.with_color(ColorStyle::primary(), |printer| {
+                for y in 0..size.y {
+                    printer.print_hline((0, y), size.x, " ");
+                }
+                view.draw(printer);
+            });
+        }
+
+        let screen = screen.borrow().clone();
+        Snapshot {
+            screen,
+            annotated: false,
+        }
+    }
+
+    /// Includes colors and effects in the snapshot.
+    ///
+    /// Chainable variant.
+    pub fn annotated(self) -> Self {
+        self.with_annotations(true)
+    }
+
+    /// Sets whether colors and effects are included in the snapshot.
+    ///
+    /// Chainable variant.
+    pub fn with_annotations(mut self, annotated: bool) -> Self {
+        self.set_annotations(annotated);
+        self
+    }
+
+    /// Sets whether colors and effects are included in the snapshot.
+    pub fn set_annotations(&mut self, annotated: bool) {
+        self.annotated = annotated;
+    }
+
+    /// Returns the rendered screen.
+    pub fn screen(&self) -> &ObservedScreen {
+        &self.screen
+    }
+
+    /// Compares this snapshot to the expected content.
+    ///
+    /// Returns a readable diff if they differ.
+    pub fn compare(&self, expected: &str) -> Result<(), String> {
+        let actual = self.to_string();
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(diff(expected, &actual))
+        }
+    }
+
+    /// Compares this snapshot to the content of the given fixture file.
+    ///
+    /// If the `CURSIVE_UPDATE_SNAPSHOTS` environment variable is set, the
+    /// fixture is (over)written instead.
+    ///
+    /// # Panics
+    ///
+    /// If the snapshot doesn't match the fixture, or if the fixture could
+    /// not be read or written. A missing fixture is an error: the rendered
+    /// output is included in the message.
+    pub fn assert_matches<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+
+        if env::var_os("CURSIVE_UPDATE_SNAPSHOTS").is_some() {
+            if let Err(e) = self.save(path) {
+                panic!("Could not write {}: {}", path.display(), e);
+            }
+            return;
+        }
+
+        if !path.exists() {
+            panic!(
+                "Missing snapshot {} (set CURSIVE_UPDATE_SNAPSHOTS to \
+                 create it). Rendered output:\n{}",
+                path.display(),
+                self
+            );
+        }
+
+        let expected = match read_file(path) {
+            Ok(content) => content,
+            Err(e) => panic!("Could not read {}: {}", path.display(), e),
+        };
+
+        if let Err(diff) = self.compare(&expected) {
+            panic!("Snapshot does not match {}:\n{}", path.display(), diff);
+        }
+    }
+
+    /// Writes this snapshot to the given file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(path)?;
+        file.write_all(self.to_string().as_bytes())
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut styles: Vec<(ColorPair, EnumSet<Effect>)> = Vec::new();
+
+        for y in 0..self.screen.size().y {
+            writeln!(f, "|{}|", self.screen.line(y))?;
+
+            if self.annotated {
+                let ids: String = self
+                    .screen
+                    .row(y)
+                    .iter()
+                    .map(|cell| style_id(&mut styles, cell))
+                    .collect();
+                writeln!(f, " {}", ids)?;
+            }
+        }
+
+        if self.annotated {
+            writeln!(f)?;
+            for (i, &(colors, effects)) in styles.iter().enumerate() {
+                let id = STYLE_IDS.chars().nth(i).unwrap_or('?');
+                write!(f, "{}: {:?} on {:?}", id, colors.front, colors.back)?;
+                for effect in effects.iter() {
+                    write!(f, " +{:?}", effect)?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the identifier for the style of the given cell.
+///
+/// New styles are added to `styles` as they are found.
+fn style_id(
+    styles: &mut Vec<(ColorPair, EnumSet<Effect>)>, cell: &ObservedCell
+) -> char {
+    let style = (cell.colors, cell.effects);
+    let i = match styles.iter().position(|s| *s == style) {
+        Some(i) => i,
+        None => {
+            styles.push(style);
+            styles.len() - 1
+        }
+    };
+    STYLE_IDS.chars().nth(i).unwrap_or('?')
+}
+
+fn read_file(path: &Path) -> io::Result<String> {
+    let mut content = String::new();
+    File::open(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Returns a line-based diff between `expected` and `actual`.
+///
+/// Removed lines start with `-`, added lines with `+`,
+/// and unchanged lines with a space.
+fn diff(expected: &str, actual: &str) -> String {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence
+    // between a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                1 + lcs[i + 1][j + 1]
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            result.push_str(&format!(" {}\n", a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len()
+            && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1])
+        {
+            result.push_str(&format!("-{}\n", a[i]));
+            i += 1;
+        } else {
+            result.push_str(&format!("+{}\n", b[j]));
+            j += 1;
+        }
+    }
+
+    result
+}
+
+/// Returns the path to the fixture with the given name.
+#[cfg(test)]
+pub(crate) fn fixture(name: &str) -> String {
+    format!(
+        "{}/src/utils/snapshot/fixtures/{}.txt",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+#[cfg(test)]
+mod tests;
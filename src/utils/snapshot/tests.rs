@@ -0,0 +1,75 @@
+use super::{diff, fixture, Snapshot};
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use views::{Button, Dialog, LinearLayout, SelectView, TextView};
+
+#[test]
+fn test_diff() {
+    let expected = "a\nb\nc\n";
+    let actual = "a\nB\nc\nd\n";
+
+    assert_eq!(diff(expected, actual), " a\n-b\n+B\n c\n+d\n");
+}
+
+#[test]
+fn test_compare() {
+    let mut view = TextView::new("Hi");
+    let snapshot = Snapshot::render(&mut view, (4, 1));
+
+    assert_eq!(snapshot.compare("|Hi  |\n"), Ok(()));
+    assert!(snapshot.compare("|Ho  |\n").is_err());
+}
+
+#[test]
+fn test_missing_fixture() {
+    if env::var_os("CURSIVE_UPDATE_SNAPSHOTS").is_some() {
+        // The fixture would be written instead.
+        return;
+    }
+
+    let mut view = TextView::new("Hi");
+    let snapshot = Snapshot::render(&mut view, (4, 1));
+    let path = fixture("does_not_exist");
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        snapshot.assert_matches(&path);
+    }));
+    assert!(result.is_err());
+    assert!(!Path::new(&path).exists());
+}
+
+#[test]
+fn test_dialog() {
+    let mut dialog = Dialog::around(TextView::new("Hello!"))
+        .title("Title")
+        .button("Ok", |_| ());
+
+    Snapshot::render(&mut dialog, (20, 7))
+        .annotated()
+        .assert_matches(fixture("dialog"));
+}
+
+#[test]
+fn test_linear_layout() {
+    let mut layout = LinearLayout::vertical()
+        .child(TextView::new("Top"))
+        .child(
+            LinearLayout::horizontal()
+                .child(Button::new("Left", |_| ()))
+                .child(Button::new("Right", |_| ())),
+        )
+        .child(TextView::new("Bottom"));
+
+    Snapshot::render(&mut layout, (20, 4)).assert_matches(fixture("linear"));
+}
+
+#[test]
+fn test_select_view() {
+    let mut select = SelectView::<usize>::new()
+        .with_all((1..20).map(|i| (format!("Item {}", i), i)));
+
+    Snapshot::render(&mut select, (12, 5))
+        .annotated()
+        .assert_matches(fixture("select"));
+}
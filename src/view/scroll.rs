@@ -33,6 +33,11 @@ pub struct ScrollBase {
 
     /// Initial position of the cursor when dragging.
     pub thumb_grab: Option<usize>,
+
+    /// Line to scroll to once the view height is known.
+    ///
+    /// Set by `scroll_to` before the first layout.
+    pub pending_scroll: Option<usize>,
 }
 
 /// Defines the scrolling behaviour on content or size change
@@ -62,6 +67,7 @@ impl ScrollBase {
             scrollbar_offset: 0,
             right_padding: 1,
             thumb_grab: None,
+            pending_scroll: None,
         }
     }
 
@@ -96,6 +102,12 @@ impl ScrollBase {
         } else {
             self.start_line = 0;
         }
+
+        if view_height > 0 {
+            if let Some(y) = self.pending_scroll.take() {
+                self.scroll_to(y);
+            }
+        }
     }
 
     /// Returns `TRUE` if the view needs to scroll.
@@ -119,7 +131,16 @@ impl ScrollBase {
     }
 
     /// Makes sure that the given line is visible, scrolling if needed.
+    ///
+    /// Before the view height is known, this is delayed until the next
+    /// call to `set_heights`.
     pub fn scroll_to(&mut self, y: usize) {
+        if self.view_height == 0 {
+            self.pending_scroll = Some(y);
+            return;
+        }
+
+        self.pending_scroll = None;
         if y >= self.start_line + self.view_height {
             self.start_line = 1 + y - self.view_height;
         } else if y < self.start_line {
@@ -232,7 +253,7 @@ impl ScrollBase {
     /// # use cursive::theme;
     /// # use cursive::backend::{self, Backend};
     /// # let scrollbase = ScrollBase::new();
-    /// # let b: Box<Backend> = backend::Concrete::init();
+    /// # let b: Box<Backend> = backend::Concrete::init().unwrap();
     /// # let t = theme::load_default();
     /// # let printer = Printer::new((5,1), &t, &b);
     /// # let printer = &printer;
@@ -290,7 +311,8 @@ impl ScrollBase {
 
             // The scrollbar thumb
             printer.with_color(color, |printer| {
-                printer.print_vline((scrollbar_x, start), height, "▒");
+                let thumb = printer.box_drawing("▒", "#");
+                printer.print_vline((scrollbar_x, start), height, thumb);
             });
         }
     }
@@ -1,6 +1,7 @@
 use Printer;
 use direction::Direction;
 use event::{Event, EventResult};
+use rect::Rect;
 use std::any::Any;
 use vec::Vec2;
 use view::{AnyView, Selector};
@@ -97,4 +98,19 @@ pub trait View: Any + AnyView {
         let _ = source;
         false
     }
+
+    /// Returns the part of the view that should be kept visible.
+    ///
+    /// `view_size` is the size given in the last call to `layout`.
+    ///
+    /// Scrolling containers, like [`ScrollView`], use it to follow the
+    /// focus. Views with a focused child or a selected row should return
+    /// its area.
+    ///
+    /// [`ScrollView`]: ../views/struct.ScrollView.html
+    ///
+    /// Default implementation returns the entire view.
+    fn important_area(&self, view_size: Vec2) -> Rect {
+        Rect::from_corners((0, 0), view_size.saturating_sub((1, 1)))
+    }
 }
@@ -133,10 +133,12 @@ impl View for Button {
 
         let style = if !self.enabled {
             ColorStyle::secondary()
-        } else if !printer.focused {
-            ColorStyle::primary()
-        } else {
+        } else if printer.focused {
             ColorStyle::highlight()
+        } else if printer.is_hovered() {
+            ColorStyle::highlight_inactive()
+        } else {
+            ColorStyle::primary()
         };
 
         let offset =
@@ -267,7 +267,7 @@ impl Dialog {
                 match event {
                     Event::Key(Key::Down)
                     | Event::Key(Key::Tab)
-                    | Event::Shift(Key::Tab) => {
+                    | Event::ModKey(Modifiers::SHIFT, Key::Tab) => {
                         // Default to leftmost button when going down.
                         self.focus = DialogFocus::Button(0);
                         EventResult::Consumed(None)
@@ -301,7 +301,7 @@ impl Dialog {
                             EventResult::Ignored
                         }
                     }
-                    Event::Shift(Key::Tab) => {
+                    Event::ModKey(Modifiers::SHIFT, Key::Tab) => {
                         if self.content.take_focus(Direction::back()) {
                             self.focus = DialogFocus::Content;
                             EventResult::Consumed(None)
@@ -408,8 +408,8 @@ impl Dialog {
                 + self.title_position
                     .get_offset(len, printer.size.x - 2 * spacing);
             printer.with_high_border(false, |printer| {
-                printer.print((x - 2, 0), "┤ ");
-                printer.print((x + len, 0), " ├");
+                printer.print((x - 2, 0), printer.box_drawing("┤ ", "+ "));
+                printer.print((x + len, 0), printer.box_drawing(" ├", " +"));
             });
 
             printer.with_color(ColorStyle::title_primary(), |p| {
@@ -1,11 +1,12 @@
 use {Cursive, Printer, With};
+use backend::CursorShape;
 use direction::Direction;
 use event::{Callback, Event, EventResult, Key, MouseEvent};
 use std::cell::RefCell;
 use std::rc::Rc;
 use theme::{ColorStyle, Effect};
 use unicode_segmentation::UnicodeSegmentation;
-use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use unicode_width::UnicodeWidthStr;
 use utils::lines::simple::{simple_prefix, simple_suffix};
 use vec::Vec2;
 use view::View;
@@ -398,24 +399,40 @@ impl EditView {
     ///
     /// You should run this callback with a `&mut Cursive`.
     pub fn insert(&mut self, ch: char) -> Callback {
+        self.insert_str(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Insert `text` at the current cursor position.
+    ///
+    /// If a maximum content width is set, only the graphemes that fit
+    /// are inserted.
+    ///
+    /// Returns a callback in response to content change.
+    /// It runs the `on_edit` callback only once, for the whole text.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn insert_str(&mut self, text: &str) -> Callback {
         // First, make sure we can actually insert anything.
-        if let Some(width) = self.max_content_width {
+        let text = match self.max_content_width {
             // XXX: we assume here that the widths are linearly additive.
             // Is that true? What about weird combined unicode thingies?
-            // Also, say the user copy+paste some content, do we want to
-            // stop halfway through a possibly split grapheme?
-            if ch.width().unwrap_or(0) + self.content.width() > width {
-                // ABORT
-                return Callback::dummy();
+            Some(width) => {
+                let available = width.saturating_sub(self.content.width());
+                &text[..simple_prefix(text, available).length]
             }
+            None => text,
+        };
+        if text.is_empty() {
+            // ABORT
+            return Callback::dummy();
         }
 
         // `make_mut` applies copy-on-write
         // It means it'll just return a ref if no one else has a ref,
         // and it will clone it into `self.content` otherwise.
 
-        Rc::make_mut(&mut self.content).insert(self.cursor, ch);
-        self.cursor += ch.len_utf8();
+        Rc::make_mut(&mut self.content).insert_str(self.cursor, text);
+        self.cursor += text.len();
 
         self.keep_cursor_in_view();
 
@@ -576,7 +593,11 @@ impl View for EditView {
             });
 
             // Now print cursor
-            if printer.focused {
+            let offset = self.content[self.offset..self.cursor].width();
+            if printer.focused && printer.capabilities().cursor {
+                printer.set_cursor((offset, 0), CursorShape::Block);
+            } else if printer.focused {
+                // Fake a cursor by printing this cell without the effect.
                 let c: &str = if self.cursor == self.content.len() {
                     &self.filler
                 } else {
@@ -594,7 +615,6 @@ impl View for EditView {
                         selected
                     }
                 };
-                let offset = self.content[self.offset..self.cursor].width();
                 printer.print((offset, 0), c);
             }
         });
@@ -614,6 +634,15 @@ impl View for EditView {
             Event::Char(ch) => {
                 return EventResult::Consumed(Some(self.insert(ch)));
             }
+            Event::Paste(ref text) => {
+                // We only have one line: newlines become spaces.
+                let text: String = text
+                    .trim_end_matches('\n')
+                    .chars()
+                    .map(|c| if c.is_control() { ' ' } else { c })
+                    .collect();
+                return EventResult::Consumed(Some(self.insert_str(&text)));
+            }
             // TODO: handle ctrl-key?
             Event::Key(Key::Home) => self.cursor = 0,
             Event::Key(Key::End) => self.cursor = self.content.len(),
@@ -679,3 +708,53 @@ impl View for EditView {
         EventResult::Consumed(self.make_edit_cb())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use std::cell::Cell;
+    use view::Identifiable;
+
+    #[test]
+    fn paste() {
+        let backend = Puppet::new((20, 1));
+        let input = backend.input();
+
+        let edits = Rc::new(Cell::new(0));
+        let submitted = Rc::new(Cell::new(false));
+
+        let mut siv = Cursive::with_backend(backend);
+        {
+            let edits = Rc::clone(&edits);
+            let submitted = Rc::clone(&submitted);
+            siv.root_mut().add_fullscreen_layer(
+                EditView::new()
+                    .on_edit(move |_, _, _| edits.set(edits.get() + 1))
+                    .on_submit(move |_, _| submitted.set(true))
+                    .with_id("edit"),
+            );
+        }
+
+        input
+            .borrow_mut()
+            .push_back(Event::Paste(String::from("Hello\nworld\n")));
+        siv.step();
+
+        let content = siv
+            .call_on_id("edit", |view: &mut EditView| view.get_content())
+            .unwrap();
+        assert_eq!(&*content, "Hello world");
+        assert_eq!(edits.get(), 1);
+        assert!(!submitted.get());
+    }
+
+    #[test]
+    fn insert_str_max_width() {
+        let mut view = EditView::new().max_content_width(5);
+        view.insert_str("abc");
+        view.insert_str("日本");
+
+        assert_eq!(&*view.get_content(), "abc日");
+    }
+}
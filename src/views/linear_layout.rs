@@ -2,7 +2,8 @@ use Printer;
 use With;
 use XY;
 use direction;
-use event::{Event, EventResult, Key};
+use event::{Event, EventResult, Key, Modifiers};
+use rect::Rect;
 use std::any::Any;
 use std::cmp::min;
 use std::ops::Deref;
@@ -351,6 +352,23 @@ impl View for LinearLayout {
         }
     }
 
+    fn important_area(&self, size: Vec2) -> Rect {
+        if self.children.is_empty() {
+            return Rect::from_corners((0, 0), size.saturating_sub((1, 1)));
+        }
+
+        // Only the focused child matters.
+        let o = self.orientation;
+        let item = ChildIterator::new(self.children.iter(), o, *size.get(o))
+            .nth(self.focus)
+            .unwrap();
+        let mut area = item.child
+            .view
+            .important_area(size.with_axis(o, item.length));
+        area.offset(o.make_vec(item.offset, 0));
+        area
+    }
+
     fn required_size(&mut self, req: Vec2) -> Vec2 {
         // Did anything change since last time?
         if let Some(size) = self.get_cache(req) {
@@ -515,7 +533,9 @@ impl View for LinearLayout {
         };
         match result {
             EventResult::Ignored => match event {
-                Event::Shift(Key::Tab) if self.focus > 0 => {
+                Event::ModKey(Modifiers::SHIFT, Key::Tab)
+                    if self.focus > 0 =>
+                {
                     self.move_focus(direction::Direction::back())
                 }
                 Event::Key(Key::Tab)
@@ -2,7 +2,9 @@ use Cursive;
 use Printer;
 use With;
 use direction;
-use event::{Callback, Event, EventResult, Key, MouseButton, MouseEvent};
+use event::{Callback, Event, EventResult, Key, Modifiers, MouseButton,
+            MouseEvent};
+use rect::Rect;
 use std::any::Any;
 use std::rc::Rc;
 use unicode_width::UnicodeWidthStr;
@@ -293,11 +295,13 @@ impl View for ListView {
             .max()
             .unwrap_or(0);
 
+        // Views given an unbounded width may ask for all of it.
+        let width = view_size.saturating_add(label_width + 1);
         if self.children.len() > req.y {
             // Include a scroll bar
-            Vec2::new(label_width + 1 + view_size + 2, req.y)
+            Vec2::new(width.saturating_add(2), req.y)
         } else {
-            Vec2::new(label_width + 1 + view_size, self.children.len())
+            Vec2::new(width, self.children.len())
         }
     }
 
@@ -326,6 +330,16 @@ impl View for ListView {
         }
     }
 
+    fn important_area(&self, size: Vec2) -> Rect {
+        if self.children.is_empty() {
+            return Rect::from_corners((0, 0), size.saturating_sub((1, 1)));
+        }
+
+        // The focused row, including its label.
+        let y = self.focus.saturating_sub(self.scrollbase.start_line);
+        Rect::from_corners((0, y), (size.x.saturating_sub(1), y))
+    }
+
     fn on_event(&mut self, event: Event) -> EventResult {
         if self.children.is_empty() {
             return EventResult::Ignored;
@@ -399,20 +413,20 @@ impl View for ListView {
             Event::Key(Key::PageDown) => {
                 self.move_focus(10, direction::Direction::up())
             }
-            Event::Key(Key::Home) | Event::Ctrl(Key::Home) => {
-                self.move_focus(
-                    usize::max_value(),
-                    direction::Direction::back(),
-                )
-            }
-            Event::Key(Key::End) | Event::Ctrl(Key::End) => self.move_focus(
+            Event::Key(Key::Home)
+            | Event::ModKey(Modifiers::CTRL, Key::Home) => self.move_focus(
+                usize::max_value(),
+                direction::Direction::back(),
+            ),
+            Event::Key(Key::End)
+            | Event::ModKey(Modifiers::CTRL, Key::End) => self.move_focus(
                 usize::max_value(),
                 direction::Direction::front(),
             ),
             Event::Key(Key::Tab) => {
                 self.move_focus(1, direction::Direction::front())
             }
-            Event::Shift(Key::Tab) => {
+            Event::ModKey(Modifiers::SHIFT, Key::Tab) => {
                 self.move_focus(1, direction::Direction::back())
             }
             Event::Mouse {
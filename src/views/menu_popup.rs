@@ -334,6 +334,23 @@ impl View for MenuPopup {
                 let position = position.saturating_sub(offset + (0, 1));
                 self.scrollbase.drag(position);
             }
+            Event::Mouse {
+                event: MouseEvent::Move,
+                position,
+                offset,
+            } => {
+                // Follow the mouse, like most menus do.
+                fix_scroll = false;
+                let inner_size = self.last_size.saturating_sub((2, 2));
+                if let Some(position) = position.checked_sub(offset + (1, 1)) {
+                    if position < inner_size {
+                        let focus = position.y + self.scrollbase.start_line;
+                        if !self.menu.children[focus].is_delimiter() {
+                            self.focus = focus;
+                        }
+                    }
+                }
+            }
             Event::Mouse {
                 event: MouseEvent::Press(_),
                 position,
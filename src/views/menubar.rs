@@ -274,9 +274,19 @@ impl View for Menubar {
             // because it's ugly on the menubar.
             let selected =
                 (self.state != State::Inactive) && (i == self.focus);
-            printer.with_selection(selected, |printer| {
-                printer.print((offset, 0), &format!(" {} ", title));
-            });
+            let label = format!(" {} ", title);
+            let printer =
+                printer.sub_printer((offset, 0), (label.width(), 1), true);
+            if printer.is_hovered() && !selected && !item.is_delimiter() {
+                let style = ColorStyle::highlight_inactive();
+                printer.with_color(style, |printer| {
+                    printer.print((0, 0), &label);
+                });
+            } else {
+                printer.with_selection(selected, |printer| {
+                    printer.print((0, 0), &label);
+                });
+            }
             offset += title.width() + 2;
         }
     }
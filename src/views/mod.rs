@@ -54,14 +54,19 @@ mod menu_popup;
 mod panel;
 mod progress_bar;
 mod radio;
+mod scroll_view;
 mod select_view;
 mod slider_view;
 mod shadow_view;
 mod sized_view;
+mod split_view;
 mod stack_view;
+mod tab_view;
+mod table_view;
 mod text_area;
 mod text_view;
 mod tracked_view;
+mod tree_view;
 
 pub use self::box_view::BoxView;
 pub use self::button::Button;
@@ -81,12 +86,17 @@ pub use self::on_event_view::OnEventView;
 pub use self::panel::Panel;
 pub use self::progress_bar::{Counter, ProgressBar};
 pub use self::radio::{RadioButton, RadioGroup};
+pub use self::scroll_view::ScrollView;
 pub use self::select_view::SelectView;
 pub use self::shadow_view::ShadowView;
 pub use self::sized_view::SizedView;
 pub use self::slider_view::SliderView;
+pub use self::split_view::SplitView;
 pub use self::stack_view::{LayerPosition, StackView};
+pub use self::tab_view::TabView;
+pub use self::table_view::{SortOrder, TableColumn, TableView};
 pub use self::text_area::TextArea;
 pub use self::text_view::{TextContent, TextContentRef, TextView};
 pub use self::tracked_view::TrackedView;
+pub use self::tree_view::{TreeEntry, TreeView};
 pub use self::view_box::ViewBox;
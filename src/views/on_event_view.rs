@@ -177,7 +177,7 @@ impl<T: View> OnEventView<T> {
         F: Fn(&mut T) -> Option<EventResult> + 'static,
     {
         self.callbacks.insert(
-            event.into(),
+            event.into().normalized(),
             Action {
                 phase: TriggerPhase::BeforeChild,
                 callback: Rc::new(Box::new(cb)),
@@ -196,7 +196,7 @@ impl<T: View> OnEventView<T> {
         F: Fn(&mut T) -> Option<EventResult> + 'static,
     {
         self.callbacks.insert(
-            event.into(),
+            event.into().normalized(),
             Action {
                 phase: TriggerPhase::AfterChild,
                 callback: Rc::new(Box::new(cb)),
@@ -211,7 +211,7 @@ impl<T: View> ViewWrapper for OnEventView<T> {
     wrap_impl!(self.view: T);
 
     fn wrap_on_event(&mut self, event: Event) -> EventResult {
-        let action = self.callbacks.get(&event).cloned();
+        let action = self.callbacks.get(&event.normalized()).cloned();
         let pre_child = action
             .as_ref()
             .map(|a| a.phase == TriggerPhase::BeforeChild)
@@ -1,5 +1,6 @@
 use Printer;
 use event::{Event, EventResult};
+use rect::Rect;
 use vec::Vec2;
 use view::{View, ViewWrapper};
 
@@ -44,4 +45,10 @@ impl<V: View> ViewWrapper for Panel<V> {
     fn wrap_layout(&mut self, size: Vec2) {
         self.view.layout(size.saturating_sub((2, 2)));
     }
+
+    fn wrap_important_area(&self, size: Vec2) -> Rect {
+        let mut area = self.view.important_area(size.saturating_sub((2, 2)));
+        area.offset((1, 1));
+        area
+    }
 }
@@ -0,0 +1,652 @@
+use Printer;
+use With;
+use XY;
+use direction::Direction;
+use event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use rect::Rect;
+use std::any::Any;
+use std::cmp::{max, min};
+use theme::ColorStyle;
+use vec::Vec2;
+use view::{ScrollBase, Selector, View};
+
+// Size offered to the child on the axes that scroll.
+//
+// Not quite `usize::MAX`, to leave room for borders or padding added
+// around a view taking all it's given. Containers adding up several such
+// views saturate instead.
+const UNBOUNDED: usize = usize::max_value() / 2;
+
+// Space taken by the scrollbars on each axis.
+//
+// The vertical scrollbar keeps a blank column before it, like in other
+// views using `ScrollBase`.
+fn scrollbar_size(scrollbars: XY<bool>) -> Vec2 {
+    Vec2::new(
+        if scrollbars.y { 2 } else { 0 },
+        if scrollbars.x { 1 } else { 0 },
+    )
+}
+
+/// Wraps a view in a scrollable area.
+///
+/// The child is given the size it requires, even if it is larger than the
+/// available space. Only the part in the viewport is shown, with a
+/// scrollbar for each axis where the child doesn't fit.
+///
+/// Views that usually scroll by themselves, like `SelectView` or
+/// `ListView`, are shown whole instead. Text doesn't wrap unless
+/// horizontal scrolling is disabled with [`scroll_x`].
+///
+/// Views taking all the space they're given, like `BoxView::full_width`,
+/// only fill the viewport.
+///
+/// Events ignored by the child can scroll the view: arrow keys,
+/// `<PageUp>`, `<PageDown>`, `<Home>`, `<End>` and the mouse wheel.
+/// Scrollbars can also be dragged with the mouse.
+///
+/// When the focus moves inside the child, the view scrolls to keep it
+/// visible (see [`View::important_area`]).
+///
+/// [`View::important_area`]: ../view/trait.View.html#method.important_area
+/// [`scroll_x`]: #method.scroll_x
+///
+/// # Examples
+///
+/// ```rust
+/// # use cursive::views::{Dialog, EditView, ListView, ScrollView};
+/// let mut form = ListView::new();
+/// for i in 0..50 {
+///     form.add_child(&format!("Field {}", i), EditView::new());
+/// }
+///
+/// let dialog = Dialog::around(ScrollView::new(form));
+/// ```
+pub struct ScrollView<V: View> {
+    inner: V,
+    // One per axis: `start_line` is the scroll offset on this axis.
+    scrollbases: XY<ScrollBase>,
+    // Axes on which the child can be larger than the viewport.
+    enabled: XY<bool>,
+    show_scrollbars: bool,
+    // Axes where a scrollbar is shown, since the last layout.
+    scrollbars: XY<bool>,
+    // Size given to the child in the last layout.
+    inner_size: Vec2,
+    last_size: Vec2,
+}
+
+impl<V: View> ScrollView<V> {
+    /// Creates a new ScrollView around `view`.
+    ///
+    /// Both axes can scroll.
+    pub fn new(view: V) -> Self {
+        ScrollView {
+            inner: view,
+            scrollbases: XY::new(ScrollBase::new(), ScrollBase::new()),
+            enabled: XY::new(true, true),
+            show_scrollbars: true,
+            scrollbars: XY::new(false, false),
+            inner_size: Vec2::zero(),
+            last_size: Vec2::zero(),
+        }
+    }
+
+    inner_getters!(self.inner: V);
+
+    /// Sets whether the view can scroll horizontally.
+    ///
+    /// Otherwise, the child is never wider than the view.
+    ///
+    /// Defaults to `true`.
+    pub fn set_scroll_x(&mut self, enabled: bool) {
+        self.enabled.x = enabled;
+    }
+
+    /// Sets whether the view can scroll horizontally.
+    ///
+    /// Chainable variant.
+    pub fn scroll_x(self, enabled: bool) -> Self {
+        self.with(|s| s.set_scroll_x(enabled))
+    }
+
+    /// Sets whether the view can scroll vertically.
+    ///
+    /// Otherwise, the child is never taller than the view.
+    ///
+    /// Defaults to `true`.
+    pub fn set_scroll_y(&mut self, enabled: bool) {
+        self.enabled.y = enabled;
+    }
+
+    /// Sets whether the view can scroll vertically.
+    ///
+    /// Chainable variant.
+    pub fn scroll_y(self, enabled: bool) -> Self {
+        self.with(|s| s.set_scroll_y(enabled))
+    }
+
+    /// Sets whether scrollbars are shown when the child doesn't fit.
+    ///
+    /// Without scrollbars, the view can still be scrolled.
+    ///
+    /// Defaults to `true`.
+    pub fn set_show_scrollbars(&mut self, show: bool) {
+        self.show_scrollbars = show;
+    }
+
+    /// Sets whether scrollbars are shown when the child doesn't fit.
+    ///
+    /// Chainable variant.
+    pub fn show_scrollbars(self, show: bool) -> Self {
+        self.with(|s| s.set_show_scrollbars(show))
+    }
+
+    /// Returns the position, in the child, of the top-left visible cell.
+    pub fn scroll_offset(&self) -> Vec2 {
+        Vec2::new(
+            self.scrollbases.x.start_line,
+            self.scrollbases.y.start_line,
+        )
+    }
+
+    /// Scrolls so the given cell of the child is at the top-left corner.
+    ///
+    /// The offset is reduced on the next layout if it goes past the end
+    /// of the child.
+    pub fn set_scroll_offset<S: Into<Vec2>>(&mut self, offset: S) {
+        let offset = offset.into();
+        self.scrollbases.x.start_line = offset.x;
+        self.scrollbases.y.start_line = offset.y;
+    }
+
+    /// Scrolls to the top of the child.
+    pub fn scroll_to_top(&mut self) {
+        self.scrollbases.y.scroll_top();
+    }
+
+    /// Scrolls to the bottom of the child.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scrollbases.y.scroll_bottom();
+    }
+
+    /// Scrolls until the important area of the child is visible.
+    ///
+    /// This is done automatically when the child handles a key event.
+    pub fn scroll_to_important_area(&mut self) {
+        let area = self.inner.important_area(self.inner_size);
+        self.scroll_to_rect(area);
+    }
+
+    fn scroll_to_rect(&mut self, rect: Rect) {
+        // If the area is too large, its top-left corner wins.
+        self.scrollbases.x.scroll_to(rect.right());
+        self.scrollbases.x.scroll_to(rect.left());
+        self.scrollbases.y.scroll_to(rect.bottom());
+        self.scrollbases.y.scroll_to(rect.top());
+    }
+
+    // Returns `true` if the child doesn't fit in the viewport.
+    fn is_scrollable(&self) -> bool {
+        self.scrollbases.x.scrollable() || self.scrollbases.y.scrollable()
+    }
+
+    // Size of the visible part of the child.
+    fn viewport(&self) -> Vec2 {
+        self.last_size
+            .saturating_sub(scrollbar_size(self.scrollbars))
+    }
+
+    // Asks the child for its size, leaving room for the scrollbars.
+    //
+    // There is no limit on the axes that can scroll.
+    //
+    // Returns the size of the child, and the axes needing a scrollbar.
+    fn inner_required_size(&mut self, req: Vec2) -> (Vec2, XY<bool>) {
+        let mut scrollbars = XY::new(false, false);
+        loop {
+            // Showing a scrollbar leaves less room, so the child may now
+            // need one on the other axis: we try again until it's stable.
+            let available = req.saturating_sub(scrollbar_size(scrollbars));
+            let request = Vec2::new(
+                if self.enabled.x { UNBOUNDED } else { available.x },
+                if self.enabled.y { UNBOUNDED } else { available.y },
+            );
+            let size = self.inner.required_size(request);
+            // A child taking all it's given only fills the viewport.
+            let size = Vec2::new(
+                if size.x >= UNBOUNDED { available.x } else { size.x },
+                if size.y >= UNBOUNDED { available.y } else { size.y },
+            );
+            let needed = XY::new(
+                scrollbars.x || size.x > available.x,
+                scrollbars.y || size.y > available.y,
+            ).zip_map(self.enabled, |needed, enabled| {
+                needed && enabled && self.show_scrollbars
+            });
+
+            if needed == scrollbars {
+                return (size, scrollbars);
+            }
+            scrollbars = needed;
+        }
+    }
+
+    fn on_scroll_event(&mut self, event: Event) -> EventResult {
+        let viewport = self.viewport();
+        let (x, y) = (&mut self.scrollbases.x, &mut self.scrollbases.y);
+        match event {
+            Event::Key(Key::Up) if y.can_scroll_up() => y.scroll_up(1),
+            Event::Key(Key::Down) if y.can_scroll_down() => y.scroll_down(1),
+            Event::Key(Key::Left) if x.can_scroll_up() => x.scroll_up(1),
+            Event::Key(Key::Right) if x.can_scroll_down() => {
+                x.scroll_down(1)
+            }
+            Event::Key(Key::PageUp) if y.can_scroll_up() => {
+                y.scroll_up(max(1, viewport.y))
+            }
+            Event::Key(Key::PageDown) if y.can_scroll_down() => {
+                y.scroll_down(max(1, viewport.y))
+            }
+            Event::Key(Key::Home) if y.can_scroll_up() => y.scroll_top(),
+            Event::Key(Key::End) if y.can_scroll_down() => y.scroll_bottom(),
+            Event::Mouse {
+                event: MouseEvent::WheelUp,
+                ..
+            } if y.can_scroll_up() =>
+            {
+                y.scroll_up(5)
+            }
+            Event::Mouse {
+                event: MouseEvent::WheelDown,
+                ..
+            } if y.can_scroll_down() =>
+            {
+                y.scroll_down(5)
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    // Handles mouse events on the scrollbars.
+    fn on_scrollbar_event(
+        &mut self, event: MouseEvent, position: Vec2
+    ) -> EventResult {
+        let viewport = self.viewport();
+        let size = self.last_size;
+        // The horizontal scrollbar is handled as a transposed vertical one.
+        let transposed = Vec2::new(position.y, position.x);
+
+        match event {
+            MouseEvent::Press(MouseButton::Left)
+                if self.scrollbars.y
+                    && position.y < viewport.y
+                    && self.scrollbases.y.start_drag(position, size.x) => {}
+            MouseEvent::Press(MouseButton::Left)
+                if self.scrollbars.x
+                    && position.x < viewport.x
+                    && self.scrollbases.x.start_drag(transposed, size.y) => {}
+            MouseEvent::Hold(MouseButton::Left)
+                if self.scrollbases.y.is_dragging() =>
+            {
+                self.scrollbases.y.drag(position)
+            }
+            MouseEvent::Hold(MouseButton::Left)
+                if self.scrollbases.x.is_dragging() =>
+            {
+                self.scrollbases.x.drag(transposed)
+            }
+            MouseEvent::Release(MouseButton::Left)
+                if self.scrollbases.x.is_dragging()
+                    || self.scrollbases.y.is_dragging() =>
+            {
+                self.scrollbases.x.release_grab();
+                self.scrollbases.y.release_grab();
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn on_inner_event(&mut self, event: Event) -> EventResult {
+        let event = match event {
+            Event::Mouse {
+                offset,
+                position,
+                event,
+            } => {
+                let inside = position
+                    .checked_sub(offset)
+                    .map(|position| position < self.viewport())
+                    .unwrap_or(false);
+                let position = match event {
+                    // The child may be dragging something.
+                    MouseEvent::Hold(_) | MouseEvent::Release(_) => position,
+                    _ if inside => position,
+                    // Don't send clicks on the scrollbars to the child.
+                    _ => return EventResult::Ignored,
+                };
+
+                // Only shift positions inside the view: others are left
+                // as they are, out of the child's area.
+                let position = if position.fits(offset) {
+                    position + self.scroll_offset()
+                } else {
+                    position
+                };
+                Event::Mouse {
+                    offset,
+                    position,
+                    event,
+                }
+            }
+            event => event,
+        };
+
+        self.inner.on_event(event)
+    }
+}
+
+impl<V: View> View for ScrollView<V> {
+    fn draw(&self, printer: &Printer) {
+        if printer.size.x == 0 || printer.size.y == 0 {
+            return;
+        }
+
+        let viewport = self.viewport();
+        {
+            let printer = printer.sub_printer((0, 0), viewport, true);
+            let printer =
+                printer.scrolled(self.scroll_offset(), self.inner_size);
+            self.inner.draw(&printer);
+        }
+
+        let color = if printer.focused {
+            ColorStyle::highlight()
+        } else {
+            ColorStyle::highlight_inactive()
+        };
+        let thumb = printer.box_drawing("▒", "#");
+
+        let scrollbase = &self.scrollbases.y;
+        if self.scrollbars.y && viewport.y > 0 && scrollbase.scrollable() {
+            let x = printer.size.x - 1;
+            let height = scrollbase.scrollbar_thumb_height();
+            let start = scrollbase.scrollbar_thumb_y(height);
+
+            printer.print_vline((x, 0), viewport.y, "|");
+            printer.with_color(color, |printer| {
+                printer.print_vline((x, start), height, thumb);
+            });
+        }
+
+        let scrollbase = &self.scrollbases.x;
+        if self.scrollbars.x && viewport.x > 0 && scrollbase.scrollable() {
+            let y = printer.size.y - 1;
+            let width = scrollbase.scrollbar_thumb_height();
+            let start = scrollbase.scrollbar_thumb_y(width);
+
+            printer.print_hline((0, y), viewport.x, "-");
+            printer.with_color(color, |printer| {
+                printer.print_hline((start, y), width, thumb);
+            });
+        }
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        let (size, scrollbars) = self.inner_required_size(req);
+        let size = size + scrollbar_size(scrollbars);
+
+        // We only ask for what's available on the axes we can scroll.
+        Vec2::new(
+            if self.enabled.x { min(size.x, req.x) } else { size.x },
+            if self.enabled.y { min(size.y, req.y) } else { size.y },
+        )
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.last_size = size;
+
+        let (inner_size, scrollbars) = self.inner_required_size(size);
+        self.scrollbars = scrollbars;
+
+        // The child fills at least the viewport, and only grows on the
+        // axes we can scroll.
+        let viewport = self.viewport();
+        self.inner_size = Vec2::new(
+            if self.enabled.x {
+                max(inner_size.x, viewport.x)
+            } else {
+                viewport.x
+            },
+            if self.enabled.y {
+                max(inner_size.y, viewport.y)
+            } else {
+                viewport.y
+            },
+        );
+        self.inner.layout(self.inner_size);
+
+        self.scrollbases
+            .x
+            .set_heights(viewport.x, self.inner_size.x);
+        self.scrollbases
+            .y
+            .set_heights(viewport.y, self.inner_size.y);
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if let Event::Mouse {
+            offset,
+            position,
+            event: mouse_event,
+        } = event
+        {
+            let position = position.saturating_sub(offset);
+            if let res @ EventResult::Consumed(_) =
+                self.on_scrollbar_event(mouse_event, position)
+            {
+                return res;
+            }
+        }
+
+        match self.on_inner_event(event.clone()) {
+            EventResult::Ignored => self.on_scroll_event(event),
+            res => {
+                // The focus may have moved.
+                if event.mouse_position().is_none() {
+                    self.scroll_to_important_area();
+                }
+                res
+            }
+        }
+    }
+
+    fn take_focus(&mut self, source: Direction) -> bool {
+        self.inner.take_focus(source) || self.is_scrollable()
+    }
+
+    fn call_on_any<'a>(
+        &mut self, selector: &Selector, callback: Box<FnMut(&mut Any) + 'a>
+    ) {
+        self.inner.call_on_any(selector, callback);
+    }
+
+    fn focus_view(&mut self, selector: &Selector) -> Result<(), ()> {
+        self.inner.focus_view(selector).map(|()| {
+            self.scroll_to_important_area();
+        })
+    }
+
+    fn important_area(&self, _: Vec2) -> Rect {
+        // Only keep the visible part of the child's important area.
+        let area = self.inner.important_area(self.inner_size);
+        let offset = self.scroll_offset();
+        let end = self.viewport().saturating_sub((1, 1));
+
+        Rect::from_corners(
+            area.top_left().saturating_sub(offset).or_min(end),
+            area.bottom_right().saturating_sub(offset).or_min(end),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::snapshot::{fixture, Snapshot};
+    use views::{BoxView, Canvas, Dialog, DummyView, EditView, LinearLayout,
+                ListView, Panel, SelectView, TextView};
+
+    fn form() -> ScrollView<LinearLayout> {
+        let mut layout = LinearLayout::vertical();
+        for _ in 0..20 {
+            layout.add_child(EditView::new());
+        }
+        ScrollView::new(layout)
+    }
+
+    #[test]
+    fn follows_focus() {
+        let mut view = form();
+        view.layout(Vec2::new(10, 5));
+        assert!(view.take_focus(Direction::none()));
+        assert_eq!(view.inner_size, Vec2::new(8, 20));
+
+        for _ in 0..10 {
+            view.on_event(Event::Key(Key::Down));
+        }
+        assert_eq!(view.scroll_offset(), Vec2::new(0, 6));
+
+        view.on_event(Event::Key(Key::Up));
+        assert_eq!(view.scroll_offset(), Vec2::new(0, 6));
+        for _ in 0..5 {
+            view.on_event(Event::Key(Key::Up));
+        }
+        assert_eq!(view.scroll_offset(), Vec2::new(0, 4));
+    }
+
+    #[test]
+    fn scrolls_instead_of_child() {
+        let mut form = ListView::new();
+        for i in 0..50 {
+            form.add_child(&format!("Field {}", i), EditView::new());
+        }
+        let mut view = ScrollView::new(form);
+        view.layout(Vec2::new(30, 10));
+        assert_eq!(view.inner_size, Vec2::new(28, 50));
+        assert_eq!(view.scrollbars, XY::new(false, true));
+
+        assert!(view.take_focus(Direction::none()));
+        for _ in 0..20 {
+            view.on_event(Event::Key(Key::Down));
+        }
+        assert_eq!(view.scroll_offset(), Vec2::new(0, 11));
+
+        let mut view = ScrollView::new(
+            SelectView::<String>::new().with_all_str((0..50).map(|i| {
+                format!("Item {}", i)
+            })),
+        );
+        view.layout(Vec2::new(30, 10));
+        assert_eq!(view.inner_size, Vec2::new(28, 50));
+        assert_eq!(view.scrollbars, XY::new(false, true));
+
+        assert!(view.take_focus(Direction::none()));
+        view.on_event(Event::Key(Key::End));
+        assert_eq!(view.scroll_offset(), Vec2::new(0, 40));
+    }
+
+    #[test]
+    fn full_children() {
+        let mut layout = LinearLayout::horizontal();
+        for _ in 0..20 {
+            layout.add_child(BoxView::with_full_width(DummyView));
+        }
+        let mut view = ScrollView::new(layout);
+        view.layout(Vec2::new(30, 10));
+        assert_eq!(view.inner_size, Vec2::new(30, 10));
+        assert_eq!(view.scrollbars, XY::new(false, false));
+
+        // Borders around such a container don't overflow.
+        let mut layout = LinearLayout::vertical();
+        for _ in 0..20 {
+            layout.add_child(Panel::new(
+                LinearLayout::horizontal()
+                    .child(BoxView::with_full_screen(DummyView))
+                    .child(BoxView::with_full_screen(DummyView)),
+            ));
+        }
+        let mut view = ScrollView::new(Dialog::around(Panel::new(layout)));
+        view.layout(Vec2::new(30, 10));
+        assert_eq!(view.inner_size, Vec2::new(30, 10));
+    }
+
+    #[test]
+    fn zero_size() {
+        // Squeezed by a parent, the view draws nothing.
+        Snapshot::render(&mut form(), (0, 5));
+        let mut view = ScrollView::new(TextView::new("Some long text"));
+        Snapshot::render(&mut view, (5, 0));
+    }
+
+    #[test]
+    fn keyboard_and_mouse() {
+        let mut view =
+            ScrollView::new(BoxView::with_fixed_size((30, 20), DummyView));
+        view.layout(Vec2::new(10, 6));
+        assert_eq!(view.scrollbars, XY::new(true, true));
+        assert_eq!(view.viewport(), Vec2::new(8, 5));
+
+        view.on_event(Event::Key(Key::End));
+        view.on_event(Event::Key(Key::Right));
+        assert_eq!(view.scroll_offset(), Vec2::new(1, 15));
+        view.on_event(Event::Key(Key::PageUp));
+        assert_eq!(view.scroll_offset(), Vec2::new(1, 10));
+
+        // At the top, the event is left to the parent.
+        view.on_event(Event::Key(Key::Home));
+        assert!(!view.on_event(Event::Key(Key::Up)).is_consumed());
+
+        let mouse = |event, x, y| Event::Mouse {
+            event,
+            position: Vec2::new(x, y),
+            offset: Vec2::zero(),
+        };
+        view.on_event(mouse(MouseEvent::WheelDown, 2, 2));
+        assert_eq!(view.scroll_offset(), Vec2::new(1, 5));
+
+        // Drag the vertical thumb to the bottom.
+        view.on_event(mouse(MouseEvent::Press(MouseButton::Left), 9, 2));
+        view.on_event(mouse(MouseEvent::Hold(MouseButton::Left), 9, 8));
+        view.on_event(mouse(MouseEvent::Release(MouseButton::Left), 9, 8));
+        assert_eq!(view.scroll_offset(), Vec2::new(1, 15));
+
+        // Click on the right end of the horizontal scrollbar.
+        view.on_event(mouse(MouseEvent::Press(MouseButton::Left), 7, 5));
+        view.on_event(mouse(MouseEvent::Release(MouseButton::Left), 7, 5));
+        assert_eq!(view.scroll_offset(), Vec2::new(22, 15));
+    }
+
+    #[test]
+    fn snapshot() {
+        let canvas = Canvas::new(())
+            .with_draw(|_, printer| {
+                for y in 0..printer.size.y {
+                    printer.print((0, y), &format!("{}: abcdefghijklmnop", y));
+                }
+                // Cut in half by the left edge.
+                printer.print((2, 4), "漢字");
+            })
+            .with_required_size(|_, _| Vec2::new(19, 10));
+        let mut view = ScrollView::new(canvas);
+        view.set_scroll_offset((3, 2));
+
+        Snapshot::render(&mut view, (12, 6))
+            .annotated()
+            .assert_matches(fixture("scroll"));
+    }
+}
@@ -5,6 +5,7 @@ use align::{Align, HAlign, VAlign};
 use direction::Direction;
 use event::{Callback, Event, EventResult, Key, MouseButton, MouseEvent};
 use menu::MenuTree;
+use rect::Rect;
 use std::borrow::Borrow;
 use std::cell::Cell;
 use std::cmp::min;
@@ -59,6 +60,8 @@ pub struct SelectView<T = String> {
     align: Align,
     // `true` if we show a one-line view, with popup on selection.
     popup: bool,
+    // `true` if only double-clicks submit.
+    double_click: bool,
     // We need the last offset to place the popup window
     // We "cache" it during the draw, so we need interior mutability.
     last_offset: Cell<Vec2>,
@@ -83,6 +86,7 @@ impl<T: 'static> SelectView<T> {
             on_submit: None,
             align: Align::top_left(),
             popup: false,
+            double_click: false,
             last_offset: Cell::new(Vec2::zero()),
             last_size: Vec2::zero(),
         }
@@ -147,11 +151,14 @@ impl<T: 'static> SelectView<T> {
         self.with(|s| s.set_on_select(cb))
     }
 
-    /// Sets a callback to be used when `<Enter>` is pressed.
+    /// Sets a callback to be used when `<Enter>` is pressed,
+    /// or when the selected item is clicked (see [`set_double_click`]).
     ///
     /// The item currently selected will be given to the callback.
     ///
     /// Here, `V` can be `T` itself, or a type that can be borrowed from `T`.
+    ///
+    /// [`set_double_click`]: #method.set_double_click
     pub fn set_on_submit<F, R, V: ?Sized>(&mut self, cb: F)
     where
         F: 'static + Fn(&mut Cursive, &V) -> R,
@@ -162,11 +169,14 @@ impl<T: 'static> SelectView<T> {
         }));
     }
 
-    /// Sets a callback to be used when `<Enter>` is pressed.
+    /// Sets a callback to be used when `<Enter>` is pressed,
+    /// or when the selected item is clicked (see [`set_double_click`]).
     ///
     /// The item currently selected will be given to the callback.
     ///
     /// Chainable variant.
+    ///
+    /// [`set_double_click`]: #method.set_double_click
     pub fn on_submit<F, V: ?Sized>(self, cb: F) -> Self
     where
         F: Fn(&mut Cursive, &V) + 'static,
@@ -175,6 +185,20 @@ impl<T: 'static> SelectView<T> {
         self.with(|s| s.set_on_submit(cb))
     }
 
+    /// Submits on double-click only, instead of on every click.
+    ///
+    /// Chainable variant.
+    pub fn double_click(self) -> Self {
+        self.with(|s| s.set_double_click(true))
+    }
+
+    /// Submits on double-click only, instead of on every click.
+    ///
+    /// A single click then only selects an item.
+    pub fn set_double_click(&mut self, double_click: bool) {
+        self.double_click = double_click;
+    }
+
     /// Sets the alignment for this view.
     pub fn align(mut self, align: Align) -> Self {
         self.align = align;
@@ -418,6 +442,22 @@ impl<T: 'static> SelectView<T> {
         EventResult::Consumed(Some(Callback::from_fn(move |s| cb(s, &v))))
     }
 
+    // Returns `true` if the mouse is over the selected item.
+    fn is_over_focus(&self, position: Vec2, offset: Vec2) -> bool {
+        let position = match position.checked_sub(offset) {
+            Some(position) => position,
+            None => return false,
+        };
+        let scrollbar_size = if self.scrollbase.scrollable() {
+            (2, 0)
+        } else {
+            (0, 0)
+        };
+        let clickable_size = self.last_size.saturating_sub(scrollbar_size);
+        position < clickable_size
+            && position.y + self.scrollbase.start_line == self.focus()
+    }
+
     fn on_event_regular(&mut self, event: Event) -> EventResult {
         let mut fix_scroll = true;
         match event {
@@ -498,22 +538,23 @@ impl<T: 'static> SelectView<T> {
             } => {
                 fix_scroll = false;
                 self.scrollbase.release_grab();
-                if self.on_submit.is_some() {
-                    if let Some(position) = position.checked_sub(offset) {
-                        let scrollbar_size = if self.scrollbase.scrollable() {
-                            (2, 0)
-                        } else {
-                            (0, 0)
-                        };
-                        let clickable_size =
-                            self.last_size.saturating_sub(scrollbar_size);
-                        if position < clickable_size
-                            && (position.y + self.scrollbase.start_line)
-                                == self.focus()
-                        {
-                            return self.submit();
-                        }
-                    }
+                if !self.double_click
+                    && self.on_submit.is_some()
+                    && self.is_over_focus(position, offset)
+                {
+                    return self.submit();
+                }
+            }
+            Event::Mouse {
+                event: MouseEvent::Click(MouseButton::Left, 2),
+                position,
+                offset,
+            } if self.double_click => {
+                fix_scroll = false;
+                if self.on_submit.is_some()
+                    && self.is_over_focus(position, offset)
+                {
+                    return self.submit();
                 }
             }
             Event::Key(Key::Enter) if self.on_submit.is_some() => {
@@ -683,10 +724,12 @@ impl<T: 'static> View for SelectView<T> {
         if self.popup {
             let style = if !self.enabled {
                 ColorStyle::secondary()
-            } else if !printer.focused {
-                ColorStyle::primary()
-            } else {
+            } else if printer.focused {
                 ColorStyle::highlight()
+            } else if printer.is_hovered() {
+                ColorStyle::highlight_inactive()
+            } else {
+                ColorStyle::primary()
             };
             let x = match printer.size.x.checked_sub(1) {
                 Some(x) => x,
@@ -720,6 +763,11 @@ impl<T: 'static> View for SelectView<T> {
                             .with_color(ColorStyle::secondary(), |printer| {
                                 self.draw_item(printer, i)
                             });
+                    } else if i != self.focus() && printer.is_hovered() {
+                        let style = ColorStyle::highlight_inactive();
+                        printer.with_color(style, |printer| {
+                            self.draw_item(printer, i)
+                        });
                     } else {
                         self.draw_item(printer, i);
                     }
@@ -772,6 +820,16 @@ impl<T: 'static> View for SelectView<T> {
             self.scrollbase.set_heights(size.y, self.items.len());
         }
     }
+
+    fn important_area(&self, size: Vec2) -> Rect {
+        if self.popup || self.items.is_empty() {
+            return Rect::from_corners((0, 0), size.saturating_sub((1, 1)));
+        }
+
+        // Only the selected row matters.
+        let y = self.focus().saturating_sub(self.scrollbase.start_line);
+        Rect::from_corners((0, y), (size.x.saturating_sub(1), y))
+    }
 }
 
 struct Item<T> {
@@ -787,3 +845,40 @@ impl<T> Item<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submits(select: &mut SelectView, event: MouseEvent) -> bool {
+        let event = Event::Mouse {
+            event,
+            position: Vec2::new(0, 1),
+            offset: Vec2::zero(),
+        };
+        match select.on_event(event) {
+            EventResult::Consumed(Some(_)) => true,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn click_submit() {
+        let mut select = SelectView::new()
+            .with_all_str(vec!["a", "b"])
+            .on_submit(|_, _: &str| ());
+        select.layout(Vec2::new(5, 2));
+
+        // The press selects "b", the release submits it.
+        assert!(!submits(&mut select, MouseEvent::Press(MouseButton::Left)));
+        assert_eq!(select.selected_id(), Some(1));
+        assert!(submits(&mut select, MouseEvent::Release(MouseButton::Left)));
+
+        select.set_double_click(true);
+        let release = MouseEvent::Release(MouseButton::Left);
+        assert!(!submits(&mut select, release));
+        let click = |n| MouseEvent::Click(MouseButton::Left, n);
+        assert!(!submits(&mut select, click(1)));
+        assert!(submits(&mut select, click(2)));
+    }
+}
@@ -0,0 +1,531 @@
+use Printer;
+use With;
+use direction::{Direction, Orientation, Relative};
+use event::{Event, EventResult, Key, Modifiers, MouseButton, MouseEvent};
+use rect::Rect;
+use std::any::Any;
+use std::cmp::{max, min};
+use theme::ColorStyle;
+use vec::Vec2;
+use view::{Selector, View};
+
+/// Shows two views side by side, separated by a movable divider.
+///
+/// A horizontal split shows its panes left and right of a vertical
+/// divider; a vertical split shows them above and below.
+///
+/// The divider can be dragged with the mouse, or moved with `<Alt>` and
+/// the arrow keys when the focused pane ignores them. `<Tab>` or the
+/// arrow keys move the focus to the other pane.
+///
+/// Each pane can have a minimum and a maximum size. The position of the
+/// divider can be saved with [`divider_position`] and restored with
+/// [`set_divider_position`].
+///
+/// A `SplitView` takes all the space available along its orientation.
+///
+/// [`divider_position`]: #method.divider_position
+/// [`set_divider_position`]: #method.set_divider_position
+///
+/// # Examples
+///
+/// ```rust
+/// # use cursive::views::{SelectView, SplitView, TextView};
+/// let files = SelectView::<String>::new();
+/// let preview = TextView::new("Select a file.");
+///
+/// let split = SplitView::horizontal(files, preview)
+///     .min_size(0, 10)
+///     .with_divider_position(20);
+/// ```
+pub struct SplitView {
+    orientation: Orientation,
+    children: [Box<View>; 2],
+    focus: usize,
+    // Requested size of the first pane, or `None` to split evenly.
+    position: Option<usize>,
+    min_sizes: [usize; 2],
+    max_sizes: [Option<usize>; 2],
+    // `true` while the divider is dragged with the mouse.
+    dragging: bool,
+    last_size: Vec2,
+}
+
+impl SplitView {
+    /// Creates a new split view with the given orientation.
+    pub fn new<A, B>(orientation: Orientation, first: A, second: B) -> Self
+    where
+        A: View + 'static,
+        B: View + 'static,
+    {
+        SplitView {
+            orientation,
+            children: [Box::new(first), Box::new(second)],
+            focus: 0,
+            position: None,
+            min_sizes: [1, 1],
+            max_sizes: [None, None],
+            dragging: false,
+            last_size: Vec2::zero(),
+        }
+    }
+
+    /// Creates a new horizontal split view, with `first` on the left.
+    pub fn horizontal<A, B>(first: A, second: B) -> Self
+    where
+        A: View + 'static,
+        B: View + 'static,
+    {
+        SplitView::new(Orientation::Horizontal, first, second)
+    }
+
+    /// Creates a new vertical split view, with `first` on top.
+    pub fn vertical<A, B>(first: A, second: B) -> Self
+    where
+        A: View + 'static,
+        B: View + 'static,
+    {
+        SplitView::new(Orientation::Vertical, first, second)
+    }
+
+    /// Returns the orientation of this view.
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns a reference to a pane.
+    ///
+    /// `0` is the first pane, `1` is the second one.
+    pub fn get_child(&self, i: usize) -> Option<&View> {
+        self.children.get(i).map(|child| &**child)
+    }
+
+    /// Returns a mutable reference to a pane.
+    ///
+    /// `0` is the first pane, `1` is the second one.
+    pub fn get_child_mut(&mut self, i: usize) -> Option<&mut View> {
+        self.children.get_mut(i).map(|child| &mut **child)
+    }
+
+    /// Returns the index of the focused pane.
+    pub fn get_focus_index(&self) -> usize {
+        self.focus
+    }
+
+    /// Sets the minimum size of a pane along the orientation.
+    ///
+    /// Defaults to 1.
+    ///
+    /// # Panics
+    ///
+    /// If `i > 1`.
+    pub fn set_min_size(&mut self, i: usize, size: usize) {
+        self.min_sizes[i] = size;
+    }
+
+    /// Sets the minimum size of a pane along the orientation.
+    ///
+    /// Chainable variant.
+    pub fn min_size(self, i: usize, size: usize) -> Self {
+        self.with(|s| s.set_min_size(i, size))
+    }
+
+    /// Sets the maximum size of a pane along the orientation.
+    ///
+    /// `None` means the pane can grow indefinitely, and is the default.
+    ///
+    /// # Panics
+    ///
+    /// If `i > 1`.
+    pub fn set_max_size(&mut self, i: usize, size: Option<usize>) {
+        self.max_sizes[i] = size;
+    }
+
+    /// Sets the maximum size of a pane along the orientation.
+    ///
+    /// Chainable variant.
+    pub fn max_size(self, i: usize, size: Option<usize>) -> Self {
+        self.with(|s| s.set_max_size(i, size))
+    }
+
+    /// Returns the size of the first pane, which is also the position of
+    /// the divider.
+    ///
+    /// This can be given to `set_divider_position` to restore the layout
+    /// later.
+    pub fn divider_position(&self) -> usize {
+        self.pane_sizes(*self.last_size.get(self.orientation)).0
+    }
+
+    /// Sets the size of the first pane.
+    ///
+    /// The limits set on each pane still apply.
+    pub fn set_divider_position(&mut self, position: usize) {
+        self.position = Some(position);
+    }
+
+    /// Sets the size of the first pane.
+    ///
+    /// Chainable variant.
+    pub fn with_divider_position(self, position: usize) -> Self {
+        self.with(|s| s.set_divider_position(position))
+    }
+
+    // Returns the size of each pane, given the total size along the
+    // orientation.
+    fn pane_sizes(&self, total: usize) -> (usize, usize) {
+        // One cell is used by the divider.
+        let available = total.saturating_sub(1);
+        let max_size = |i: usize| {
+            self.max_sizes[i].unwrap_or_else(usize::max_value)
+        };
+
+        // The minimum sizes win over the maximum ones.
+        let lowest =
+            max(self.min_sizes[0], available.saturating_sub(max_size(1)));
+        let highest =
+            min(max_size(0), available.saturating_sub(self.min_sizes[1]));
+
+        let first = self.position.unwrap_or(available / 2);
+        let first = min(max(min(first, highest), lowest), available);
+        (first, available - first)
+    }
+
+    // Moves the divider, within the limits of each pane.
+    fn drag_divider(&mut self, position: usize) {
+        self.position = Some(position);
+        // Don't keep a position out of bounds: it would jump back there
+        // when the view grows.
+        self.position = Some(self.divider_position());
+    }
+
+    fn move_divider(&mut self, delta: isize) -> EventResult {
+        let position = self.divider_position() as isize + delta;
+        self.drag_divider(max(position, 0) as usize);
+
+        EventResult::Consumed(None)
+    }
+
+    // Returns the pane under the given position, if any.
+    fn pane_at(&self, position: Vec2) -> Option<usize> {
+        let divider = self.divider_position();
+        let position = *position.get(self.orientation);
+        if position < divider {
+            Some(0)
+        } else if position > divider {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    // Offset of the given pane.
+    fn offset(&self, i: usize) -> Vec2 {
+        let offset = if i == 0 {
+            0
+        } else {
+            self.divider_position() + 1
+        };
+        self.orientation.make_vec(offset, 0)
+    }
+
+    // Size of the given pane.
+    fn child_size(&self, i: usize, size: Vec2) -> Vec2 {
+        let (first, second) = self.pane_sizes(*size.get(self.orientation));
+        size.with_axis(self.orientation, if i == 0 { first } else { second })
+    }
+
+    fn move_focus(&mut self, i: usize, source: Direction) -> EventResult {
+        if self.children[i].take_focus(source) {
+            self.focus = i;
+            EventResult::Consumed(None)
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn on_mouse_event(
+        &mut self, event: MouseEvent, position: Vec2
+    ) -> EventResult {
+        let orientation = self.orientation;
+        match event {
+            MouseEvent::Press(MouseButton::Left)
+                if self.pane_at(position).is_none() =>
+            {
+                self.dragging = true;
+            }
+            MouseEvent::Hold(MouseButton::Left) if self.dragging => {
+                self.drag_divider(*position.get(orientation));
+            }
+            MouseEvent::Release(MouseButton::Left) if self.dragging => {
+                self.dragging = false;
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn on_key_event(&mut self, event: Event) -> EventResult {
+        let horizontal = self.orientation == Orientation::Horizontal;
+        match event {
+            Event::ModKey(Modifiers::ALT, Key::Left) if horizontal => {
+                self.move_divider(-1)
+            }
+            Event::ModKey(Modifiers::ALT, Key::Right) if horizontal => {
+                self.move_divider(1)
+            }
+            Event::ModKey(Modifiers::ALT, Key::Up) if !horizontal => {
+                self.move_divider(-1)
+            }
+            Event::ModKey(Modifiers::ALT, Key::Down) if !horizontal => {
+                self.move_divider(1)
+            }
+            Event::Key(Key::Tab) if self.focus == 0 => {
+                self.move_focus(1, Direction::front())
+            }
+            Event::ModKey(Modifiers::SHIFT, Key::Tab) if self.focus == 1 => {
+                self.move_focus(0, Direction::back())
+            }
+            Event::Key(Key::Right) if horizontal && self.focus == 0 => {
+                self.move_focus(1, Direction::left())
+            }
+            Event::Key(Key::Left) if horizontal && self.focus == 1 => {
+                self.move_focus(0, Direction::right())
+            }
+            Event::Key(Key::Down) if !horizontal && self.focus == 0 => {
+                self.move_focus(1, Direction::up())
+            }
+            Event::Key(Key::Up) if !horizontal && self.focus == 1 => {
+                self.move_focus(0, Direction::down())
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+}
+
+impl View for SplitView {
+    fn draw(&self, printer: &Printer) {
+        let divider = self.divider_position();
+
+        for i in 0..2 {
+            self.children[i].draw(&printer.sub_printer(
+                self.offset(i),
+                self.child_size(i, printer.size),
+                i == self.focus,
+            ));
+        }
+
+        let o = self.orientation;
+        let size = o.make_vec(1, *printer.size.get(o.swap()));
+        let printer = printer.sub_printer(o.make_vec(divider, 0), size, true);
+        let color = if self.dragging {
+            ColorStyle::highlight()
+        } else if printer.is_hovered() {
+            ColorStyle::highlight_inactive()
+        } else {
+            ColorStyle::primary()
+        };
+        printer.with_color(color, |printer| match self.orientation {
+            Orientation::Horizontal => printer.print_vline(
+                (0, 0),
+                printer.size.y,
+                printer.box_drawing("│", "|"),
+            ),
+            Orientation::Vertical => printer.print_hline(
+                (0, 0),
+                printer.size.x,
+                printer.box_drawing("─", "-"),
+            ),
+        });
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        let o = self.orientation;
+        let sizes: Vec<Vec2> = (0..2)
+            .map(|i| {
+                let size = self.child_size(i, req);
+                self.children[i].required_size(size)
+            })
+            .collect();
+
+        // We take everything along the orientation.
+        let other = max(*sizes[0].get(o.swap()), *sizes[1].get(o.swap()));
+        o.make_vec(*req.get(o), other)
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.last_size = size;
+        for i in 0..2 {
+            let size = self.child_size(i, size);
+            self.children[i].layout(size);
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if let Event::Mouse {
+            offset,
+            position,
+            event: mouse_event,
+        } = event
+        {
+            if let Some(position) = position.checked_sub(offset) {
+                if let res @ EventResult::Consumed(_) =
+                    self.on_mouse_event(mouse_event, position)
+                {
+                    return res;
+                }
+
+                // Clicking on a pane gives it the focus.
+                if mouse_event.grabs_focus() {
+                    if let Some(i) = self.pane_at(position) {
+                        if i != self.focus
+                            && self.children[i].take_focus(Direction::none())
+                        {
+                            self.focus = i;
+                        }
+                    }
+                }
+            }
+        }
+
+        let focus = self.focus;
+        let offset = self.offset(focus);
+        match self.children[focus].on_event(event.relativized(offset)) {
+            EventResult::Ignored => self.on_key_event(event),
+            res => res,
+        }
+    }
+
+    fn take_focus(&mut self, source: Direction) -> bool {
+        // Coming from the end, try the second pane first.
+        let order = match source.relative(self.orientation) {
+            Some(Relative::Back) => [1, 0],
+            _ => [0, 1],
+        };
+
+        for &i in &order {
+            if self.children[i].take_focus(source) {
+                self.focus = i;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn call_on_any<'a>(
+        &mut self, selector: &Selector,
+        mut callback: Box<FnMut(&mut Any) + 'a>,
+    ) {
+        for child in &mut self.children {
+            child.call_on_any(selector, Box::new(|any| callback(any)));
+        }
+    }
+
+    fn focus_view(&mut self, selector: &Selector) -> Result<(), ()> {
+        for i in 0..2 {
+            if self.children[i].focus_view(selector).is_ok() {
+                self.focus = i;
+                return Ok(());
+            }
+        }
+
+        Err(())
+    }
+
+    fn important_area(&self, size: Vec2) -> Rect {
+        let mut area = self.children[self.focus]
+            .important_area(self.child_size(self.focus, size));
+        area.offset(self.offset(self.focus));
+        area
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::snapshot::{fixture, Snapshot};
+    use views::{DummyView, EditView, SelectView, TextView};
+
+    #[test]
+    fn pane_limits() {
+        let mut split = SplitView::horizontal(DummyView, DummyView)
+            .min_size(0, 5)
+            .max_size(1, Some(10));
+        split.layout(Vec2::new(30, 5));
+        assert_eq!(split.divider_position(), 19);
+
+        split.set_max_size(1, None);
+        assert_eq!(split.divider_position(), 14);
+        split.set_divider_position(2);
+        assert_eq!(split.divider_position(), 5);
+        split.set_divider_position(40);
+        assert_eq!(split.divider_position(), 28);
+
+        // The saved position is kept on a new view.
+        let position = split.divider_position();
+        let mut split = SplitView::horizontal(DummyView, DummyView)
+            .with_divider_position(position);
+        split.layout(Vec2::new(40, 5));
+        assert_eq!(split.divider_position(), 28);
+    }
+
+    #[test]
+    fn keyboard() {
+        let mut split = SplitView::vertical(EditView::new(), EditView::new());
+        split.layout(Vec2::new(10, 11));
+        assert!(split.take_focus(Direction::none()));
+        assert_eq!(split.divider_position(), 5);
+
+        split.on_event(Event::ModKey(Modifiers::ALT, Key::Down));
+        split.on_event(Event::ModKey(Modifiers::ALT, Key::Down));
+        assert_eq!(split.divider_position(), 7);
+        split.on_event(Event::ModKey(Modifiers::ALT, Key::Left));
+        assert_eq!(split.divider_position(), 7);
+
+        split.on_event(Event::Key(Key::Down));
+        assert_eq!(split.get_focus_index(), 1);
+        split.on_event(Event::ModKey(Modifiers::SHIFT, Key::Tab));
+        assert_eq!(split.get_focus_index(), 0);
+    }
+
+    #[test]
+    fn mouse_drag() {
+        let mut split =
+            SplitView::horizontal(EditView::new(), EditView::new());
+        split.layout(Vec2::new(21, 3));
+        assert!(split.take_focus(Direction::none()));
+
+        let mouse = |event, x| Event::Mouse {
+            event,
+            position: Vec2::new(x, 1),
+            offset: Vec2::zero(),
+        };
+        split.on_event(mouse(MouseEvent::Press(MouseButton::Left), 10));
+        split.on_event(mouse(MouseEvent::Hold(MouseButton::Left), 4));
+        split.on_event(mouse(MouseEvent::Release(MouseButton::Left), 4));
+        split.layout(Vec2::new(21, 3));
+        assert_eq!(split.divider_position(), 4);
+        assert_eq!(split.get_focus_index(), 0);
+
+        // Clicking on a pane focuses it.
+        split.on_event(mouse(MouseEvent::Press(MouseButton::Left), 12));
+        assert_eq!(split.get_focus_index(), 1);
+    }
+
+    #[test]
+    fn snapshot() {
+        let files = SelectView::new().with_all_str(vec!["main.rs", "lib.rs"]);
+        let preview = SplitView::vertical(
+            TextView::new("fn main() {}"),
+            TextView::new("Output"),
+        );
+        let mut split = SplitView::horizontal(files, preview)
+            .min_size(0, 4)
+            .with_divider_position(8);
+
+        Snapshot::render(&mut split, (24, 5))
+            .annotated()
+            .assert_matches(fixture("split"));
+    }
+}
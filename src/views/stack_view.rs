@@ -411,11 +411,13 @@ impl StackView {
                 StackPositionIterator::new(self.layers.iter(), printer.size)
                     .enumerate()
             {
-                v.view.draw(&printer.sub_printer(
-                    offset,
-                    v.size,
-                    i + 1 == last,
-                ));
+                let mut printer =
+                    printer.sub_printer(offset, v.size, i + 1 == last);
+                // Only the top layer receives mouse events.
+                if i + 1 != last {
+                    printer.set_mouse_position(None);
+                }
+                v.view.draw(&printer);
             }
         });
     }
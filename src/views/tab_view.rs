@@ -0,0 +1,653 @@
+use Cursive;
+use Printer;
+use With;
+use direction::Direction;
+use event::{Callback, Event, EventResult, Key, Modifiers, MouseButton,
+            MouseEvent};
+use std::any::Any;
+use std::rc::Rc;
+use unicode_width::UnicodeWidthStr;
+use vec::Vec2;
+use view::{Selector, View};
+
+// Height of the tab strip, including the line under the titles.
+const STRIP_HEIGHT: usize = 2;
+
+struct Tab {
+    title: String,
+    view: Box<View>,
+}
+
+impl Tab {
+    // Width taken in the strip by this tab, including its separator.
+    fn width(&self) -> usize {
+        self.title.width() + 3
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TabFocus {
+    // The row of titles is selected.
+    Strip,
+    // The active view is selected.
+    Content,
+}
+
+/// Shows one view at a time, with a row of titles to switch between them.
+///
+/// Each tab has a title and a view. Only the view of the active tab is
+/// shown and receives events.
+///
+/// `<Ctrl+PageUp>` and `<Ctrl+PageDown>` go to the previous or next tab,
+/// and `<Alt+1>` to `<Alt+9>` go to one of the first nine tabs. Clicking
+/// on a title also activates it.
+///
+/// When the row of titles is selected (for instance with `<Up>` from the
+/// active view), `<Left>` and `<Right>` switch tabs, and `<Down>` or
+/// `<Enter>` go back to the active view.
+///
+/// # Examples
+///
+/// ```rust
+/// # use cursive::views::{Dialog, EditView, TabView, TextView};
+/// let tabs = TabView::new()
+///     .tab("General", EditView::new())
+///     .tab("About", TextView::new("Version 1.0"));
+///
+/// let dialog = Dialog::around(tabs).button("Ok", |s| s.quit());
+/// ```
+pub struct TabView {
+    tabs: Vec<Tab>,
+    active: usize,
+    focus: TabFocus,
+    // This callback is called when the active tab is changed.
+    on_change: Option<Rc<Fn(&mut Cursive, usize)>>,
+    last_size: Vec2,
+}
+
+impl Default for TabView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TabView {
+    /// Creates a new TabView without any tab.
+    pub fn new() -> Self {
+        TabView {
+            tabs: Vec::new(),
+            active: 0,
+            focus: TabFocus::Content,
+            on_change: None,
+            last_size: Vec2::zero(),
+        }
+    }
+
+    /// Sets a callback to be used when the active tab is changed.
+    ///
+    /// The index of the new active tab will be given to the callback.
+    pub fn set_on_change<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, usize) + 'static,
+    {
+        self.on_change = Some(Rc::new(cb));
+    }
+
+    /// Sets a callback to be used when the active tab is changed.
+    ///
+    /// Chainable variant.
+    pub fn on_change<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, usize) + 'static,
+    {
+        self.with(|s| s.set_on_change(cb))
+    }
+
+    /// Adds a tab at the end, with the given title and view.
+    pub fn add_tab<S, V>(&mut self, title: S, view: V)
+    where
+        S: Into<String>,
+        V: View + 'static,
+    {
+        self.tabs.push(Tab {
+            title: title.into(),
+            view: Box::new(view),
+        });
+    }
+
+    /// Adds a tab at the end, with the given title and view.
+    ///
+    /// Chainable variant.
+    pub fn tab<S, V>(self, title: S, view: V) -> Self
+    where
+        S: Into<String>,
+        V: View + 'static,
+    {
+        self.with(|s| s.add_tab(title, view))
+    }
+
+    /// Inserts a tab at the given position.
+    ///
+    /// The active tab doesn't change.
+    ///
+    /// # Panics
+    ///
+    /// If `i > self.len()`.
+    pub fn insert_tab<S, V>(&mut self, i: usize, title: S, view: V)
+    where
+        S: Into<String>,
+        V: View + 'static,
+    {
+        self.tabs.insert(
+            i,
+            Tab {
+                title: title.into(),
+                view: Box::new(view),
+            },
+        );
+        if i <= self.active && self.tabs.len() > 1 {
+            self.active += 1;
+        }
+    }
+
+    /// Removes a tab, and returns its view.
+    ///
+    /// If it was active, the next tab (or the last one) becomes active.
+    ///
+    /// # Panics
+    ///
+    /// If `i >= self.len()`.
+    pub fn remove_tab(&mut self, i: usize) -> Box<View> {
+        let tab = self.tabs.remove(i);
+        let was_active = i == self.active;
+        if i < self.active || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        if was_active && !self.tabs.is_empty() {
+            self.focus_active();
+        }
+        tab.view
+    }
+
+    /// Returns the number of tabs.
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    /// Returns `true` if there is no tab.
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    /// Returns the title of the given tab.
+    pub fn tab_title(&self, i: usize) -> Option<&str> {
+        self.tabs.get(i).map(|tab| tab.title.as_str())
+    }
+
+    /// Changes the title of the given tab.
+    ///
+    /// # Panics
+    ///
+    /// If `i >= self.len()`.
+    pub fn set_tab_title<S: Into<String>>(&mut self, i: usize, title: S) {
+        self.tabs[i].title = title.into();
+    }
+
+    /// Returns a reference to the view of a tab.
+    pub fn get_tab(&self, i: usize) -> Option<&View> {
+        self.tabs.get(i).map(|tab| &*tab.view)
+    }
+
+    /// Returns a mutable reference to the view of a tab.
+    pub fn get_tab_mut(&mut self, i: usize) -> Option<&mut View> {
+        self.tabs.get_mut(i).map(|tab| &mut *tab.view)
+    }
+
+    /// Returns the index of the active tab.
+    ///
+    /// Returns `None` if there is no tab.
+    pub fn active_tab(&self) -> Option<usize> {
+        if self.tabs.is_empty() {
+            None
+        } else {
+            Some(self.active)
+        }
+    }
+
+    /// Makes the given tab active.
+    ///
+    /// Returns a callback in response to the change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    ///
+    /// # Panics
+    ///
+    /// If `i >= self.len()`.
+    pub fn set_active_tab(&mut self, i: usize) -> Callback {
+        assert!(i < self.tabs.len(), "Tab index out of bounds");
+        self.active = i;
+        self.focus_active();
+
+        self.make_change_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Makes the given tab active.
+    ///
+    /// Chainable variant.
+    ///
+    /// Does not apply `on_change` callbacks.
+    pub fn with_active_tab(self, i: usize) -> Self {
+        self.with(|s| {
+            s.set_active_tab(i);
+        })
+    }
+
+    // Gives the focus to the active view, if the content had it.
+    //
+    // The strip is selected instead if the view refuses the focus.
+    fn focus_active(&mut self) {
+        if self.focus == TabFocus::Content
+            && !self.tabs[self.active].view.take_focus(Direction::none())
+        {
+            self.focus = TabFocus::Strip;
+        }
+    }
+
+    /// Returns a callback from the active tab change.
+    fn make_change_cb(&self) -> Option<Callback> {
+        let active = self.active;
+        self.on_change
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, active)))
+    }
+
+    fn switch_to(&mut self, i: usize) -> EventResult {
+        if i == self.active {
+            return EventResult::Consumed(None);
+        }
+        self.set_active_tab(i);
+        EventResult::Consumed(self.make_change_cb())
+    }
+
+    // Returns the first tab shown in the strip, and the position of each
+    // tab from there, so that the active one fits in the given width.
+    fn strip_layout(&self, width: usize) -> (usize, Vec<usize>) {
+        let mut first = 0;
+        let mut end: usize =
+            self.tabs[..=self.active].iter().map(Tab::width).sum();
+        while first < self.active && end > width {
+            end -= self.tabs[first].width();
+            first += 1;
+        }
+
+        let mut x = 0;
+        let offsets = self.tabs[first..]
+            .iter()
+            .map(|tab| {
+                let offset = x;
+                x += tab.width();
+                offset
+            })
+            .collect();
+
+        (first, offsets)
+    }
+
+    fn on_strip_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Left) if self.active > 0 => {
+                let active = self.active;
+                self.switch_to(active - 1)
+            }
+            Event::Key(Key::Right) if self.active + 1 < self.tabs.len() => {
+                let active = self.active;
+                self.switch_to(active + 1)
+            }
+            Event::Key(Key::Down)
+            | Event::Key(Key::Enter)
+            | Event::Key(Key::Tab) => {
+                if self.tabs[self.active].view.take_focus(Direction::up()) {
+                    self.focus = TabFocus::Content;
+                    EventResult::Consumed(None)
+                } else {
+                    EventResult::Ignored
+                }
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn on_content_event(&mut self, event: Event) -> EventResult {
+        match self.tabs[self.active]
+            .view
+            .on_event(event.relativized((0, STRIP_HEIGHT)))
+        {
+            EventResult::Ignored => match event {
+                Event::Key(Key::Up)
+                | Event::ModKey(Modifiers::SHIFT, Key::Tab) => {
+                    self.focus = TabFocus::Strip;
+                    EventResult::Consumed(None)
+                }
+                _ => EventResult::Ignored,
+            },
+            res => res,
+        }
+    }
+
+    // Handles the shortcuts available wherever the focus is.
+    fn on_shortcut(&mut self, event: &Event) -> EventResult {
+        let len = self.tabs.len();
+        match *event {
+            Event::ModKey(Modifiers::CTRL, Key::PageUp) if len > 1 => {
+                let active = self.active;
+                self.switch_to((active + len - 1) % len)
+            }
+            Event::ModKey(Modifiers::CTRL, Key::PageDown) if len > 1 => {
+                let active = self.active;
+                self.switch_to((active + 1) % len)
+            }
+            Event::ModChar(Modifiers::ALT, c) => match c.to_digit(10) {
+                Some(n) if n >= 1 && (n as usize) <= len => {
+                    self.switch_to(n as usize - 1)
+                }
+                _ => EventResult::Ignored,
+            },
+            _ => EventResult::Ignored,
+        }
+    }
+
+    // Handles a mouse press, if it happened on the strip.
+    fn on_strip_press(&mut self, position: Vec2) -> Option<EventResult> {
+        if position.y >= STRIP_HEIGHT {
+            return None;
+        }
+        if position.y > 0 {
+            // Only titles can be clicked, not the line below them.
+            return Some(EventResult::Ignored);
+        }
+        let (first, offsets) = self.strip_layout(self.last_size.x);
+        let clicked = self.tabs[first..]
+            .iter()
+            .zip(offsets)
+            .position(|(tab, x)| {
+                position.x >= x && position.x < x + tab.width()
+            })
+            .map(|i| i + first);
+
+        Some(match clicked {
+            Some(i) => {
+                self.focus = TabFocus::Strip;
+                self.switch_to(i)
+            }
+            None => EventResult::Ignored,
+        })
+    }
+}
+
+impl View for TabView {
+    fn draw(&self, printer: &Printer) {
+        if self.tabs.is_empty() {
+            return;
+        }
+
+        printer.print_hline(
+            (0, 1),
+            printer.size.x,
+            printer.box_drawing("─", "-"),
+        );
+
+        let (first, offsets) = self.strip_layout(printer.size.x);
+        let tabs = self.tabs[first..].iter().zip(offsets);
+        for (i, (tab, x)) in tabs.enumerate() {
+            let label = format!(" {} ", tab.title);
+            if i + first == self.active {
+                let printer = printer.sub_printer(
+                    (x, 0),
+                    (label.width(), 1),
+                    self.focus == TabFocus::Strip,
+                );
+                printer.with_selection(true, |printer| {
+                    printer.print((0, 0), &label);
+                });
+            } else {
+                printer.print((x, 0), &label);
+            }
+            let x = x + label.width();
+            printer.print((x, 0), printer.box_drawing("│", "|"));
+            printer.print((x, 1), printer.box_drawing("┴", "+"));
+        }
+
+        self.tabs[self.active].view.draw(&printer.sub_printer(
+            (0, STRIP_HEIGHT),
+            printer.size.saturating_sub((0, STRIP_HEIGHT)),
+            self.focus == TabFocus::Content,
+        ));
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        let strip_width: usize = self.tabs.iter().map(Tab::width).sum();
+
+        // Take the largest tab, so the size doesn't change when switching.
+        let content_req = req.saturating_sub((0, STRIP_HEIGHT));
+        let content = self.tabs
+            .iter_mut()
+            .map(|tab| tab.view.required_size(content_req))
+            .fold(Vec2::zero(), Vec2::max);
+
+        Vec2::new(
+            content.x.max(strip_width.min(req.x)),
+            content.y + STRIP_HEIGHT,
+        )
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.last_size = size;
+        if let Some(tab) = self.tabs.get_mut(self.active) {
+            tab.view.layout(size.saturating_sub((0, STRIP_HEIGHT)));
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if self.tabs.is_empty() {
+            return EventResult::Ignored;
+        }
+
+        if let Event::Mouse {
+            offset,
+            position,
+            event: mouse_event,
+        } = event
+        {
+            if let Some(position) = position.checked_sub(offset) {
+                if mouse_event == MouseEvent::Press(MouseButton::Left) {
+                    if let Some(res) = self.on_strip_press(position) {
+                        return res;
+                    }
+                }
+                if mouse_event.grabs_focus()
+                    && position.y >= STRIP_HEIGHT
+                    && self.focus == TabFocus::Strip
+                    && self.tabs[self.active]
+                        .view
+                        .take_focus(Direction::none())
+                {
+                    self.focus = TabFocus::Content;
+                }
+            }
+        }
+
+        let result = match self.focus {
+            TabFocus::Strip => self.on_strip_event(event.clone()),
+            TabFocus::Content => self.on_content_event(event.clone()),
+        };
+        match result {
+            EventResult::Ignored => self.on_shortcut(&event),
+            res => res,
+        }
+    }
+
+    fn take_focus(&mut self, source: Direction) -> bool {
+        if self.tabs.is_empty() {
+            return false;
+        }
+
+        // Coming from above, we land on the titles first.
+        self.focus = if source != Direction::up()
+            && self.tabs[self.active].view.take_focus(source)
+        {
+            TabFocus::Content
+        } else {
+            TabFocus::Strip
+        };
+        true
+    }
+
+    fn call_on_any<'a>(
+        &mut self, selector: &Selector,
+        mut callback: Box<FnMut(&mut Any) + 'a>,
+    ) {
+        for tab in &mut self.tabs {
+            tab.view
+                .call_on_any(selector, Box::new(|any| callback(any)));
+        }
+    }
+
+    fn focus_view(&mut self, selector: &Selector) -> Result<(), ()> {
+        for (i, tab) in self.tabs.iter_mut().enumerate() {
+            if tab.view.focus_view(selector).is_ok() {
+                self.active = i;
+                self.focus = TabFocus::Content;
+                return Ok(());
+            }
+        }
+
+        Err(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use std::cell::Cell;
+    use utils::snapshot::{fixture, Snapshot};
+    use view::Identifiable;
+    use views::{Dialog, EditView, TextView};
+
+    fn tabs() -> TabView {
+        TabView::new()
+            .tab("One", EditView::new())
+            .tab("Two", TextView::new("2"))
+            .tab("Three", EditView::new())
+    }
+
+    #[test]
+    fn keyboard_shortcuts() {
+        let mut tabs = tabs();
+        tabs.layout(Vec2::new(30, 5));
+        assert!(tabs.take_focus(Direction::none()));
+
+        tabs.on_event(Event::ModKey(Modifiers::CTRL, Key::PageUp));
+        assert_eq!(tabs.active_tab(), Some(2));
+        tabs.on_event(Event::ModKey(Modifiers::CTRL, Key::PageDown));
+        assert_eq!(tabs.active_tab(), Some(0));
+        tabs.on_event(Event::ModChar(Modifiers::ALT, '3'));
+        assert_eq!(tabs.active_tab(), Some(2));
+        tabs.on_event(Event::ModChar(Modifiers::ALT, '4'));
+        assert_eq!(tabs.active_tab(), Some(2));
+
+        // Up from the content selects the strip, where Left and Right
+        // switch tabs.
+        tabs.on_event(Event::Key(Key::Up));
+        assert_eq!(tabs.focus, TabFocus::Strip);
+        tabs.on_event(Event::Key(Key::Left));
+        tabs.on_event(Event::Key(Key::Left));
+        assert_eq!(tabs.active_tab(), Some(0));
+        tabs.on_event(Event::Key(Key::Down));
+        assert_eq!(tabs.focus, TabFocus::Content);
+
+        // The second tab cannot take the focus.
+        tabs.on_event(Event::ModChar(Modifiers::ALT, '2'));
+        assert_eq!(tabs.focus, TabFocus::Strip);
+    }
+
+    #[test]
+    fn mouse_click() {
+        let backend = Puppet::new((30, 6));
+        let input = backend.input();
+        let changes = Rc::new(Cell::new(0));
+
+        let mut siv = Cursive::with_backend(backend);
+        {
+            let changes = Rc::clone(&changes);
+            siv.root_mut().add_fullscreen_layer(
+                tabs()
+                    .on_change(move |_, i| changes.set(changes.get() + i))
+                    .with_id("tabs"),
+            );
+        }
+        let click = |position| Event::Mouse {
+            event: MouseEvent::Press(MouseButton::Left),
+            position,
+            offset: Vec2::zero(),
+        };
+
+        // " One │ Two │ Three │": "Three" uses cells 12 to 19.
+        // The line below the titles is not clickable.
+        input.borrow_mut().extend(vec![
+            click(Vec2::new(8, 1)),
+            click(Vec2::new(14, 0)),
+            click(Vec2::new(25, 0)),
+        ]);
+        for _ in 0..4 {
+            siv.step();
+        }
+
+        siv.call_on_id("tabs", |tabs: &mut TabView| {
+            assert_eq!(tabs.active_tab(), Some(2));
+        });
+        assert_eq!(changes.get(), 2);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut tabs = tabs().with_active_tab(1);
+
+        tabs.insert_tab(0, "Zero", TextView::new("0"));
+        assert_eq!(tabs.active_tab(), Some(2));
+        assert_eq!(tabs.tab_title(2), Some("Two"));
+
+        tabs.remove_tab(0);
+        tabs.remove_tab(1);
+        assert_eq!(tabs.active_tab(), Some(1));
+        assert_eq!(tabs.tab_title(1), Some("Three"));
+
+        tabs.remove_tab(1);
+        tabs.remove_tab(0);
+        assert_eq!(tabs.active_tab(), None);
+
+        // A view refusing the focus leaves the strip selected.
+        let mut tabs = TabView::new()
+            .tab("One", EditView::new())
+            .tab("Two", TextView::new("2"));
+        assert!(tabs.take_focus(Direction::none()));
+        assert_eq!(tabs.focus, TabFocus::Content);
+        tabs.remove_tab(0);
+        assert_eq!(tabs.focus, TabFocus::Strip);
+    }
+
+    #[test]
+    fn snapshot() {
+        let tabs = TabView::new()
+            .tab("General", EditView::new().content("cursive"))
+            .tab("Advanced", TextView::new("Nothing to see here."))
+            .tab("About", TextView::new("Version 0.8"))
+            .with_active_tab(1);
+        let mut dialog =
+            Dialog::around(tabs).title("Settings").button("Ok", |_| ());
+
+        Snapshot::render(&mut dialog, (32, 9))
+            .annotated()
+            .assert_matches(fixture("tabs"));
+    }
+}
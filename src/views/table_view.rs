@@ -0,0 +1,1067 @@
+use Cursive;
+use Printer;
+use With;
+use align::HAlign;
+use direction::Direction;
+use event::{Callback, Event, EventResult, Key, Modifiers, MouseButton,
+            MouseEvent};
+use std::borrow::Borrow;
+use std::cmp::{max, min, Ordering};
+use std::rc::Rc;
+use theme::ColorStyle;
+use unicode_width::UnicodeWidthStr;
+use vec::Vec2;
+use view::{ScrollBase, SizeConstraint, View};
+
+/// Number of cells between two columns.
+const SEPARATOR_WIDTH: usize = 3;
+
+/// Number of rows used by the header and the line below it.
+const HEADER_HEIGHT: usize = 2;
+
+/// Order in which the rows of a [`TableView`] are sorted.
+///
+/// [`TableView`]: struct.TableView.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Smallest values first.
+    Ascending,
+    /// Largest values first.
+    Descending,
+}
+
+/// Column of a [`TableView`].
+///
+/// [`TableView`]: struct.TableView.html
+pub struct TableColumn<T> {
+    title: String,
+    text: Box<Fn(&T) -> String>,
+    cmp: Option<Box<Fn(&T, &T) -> Ordering>>,
+    align: HAlign,
+    width: SizeConstraint,
+    // Width needed to show the title and every row.
+    content_width: usize,
+    // Width given during the last layout.
+    size: usize,
+}
+
+impl<T> TableColumn<T> {
+    /// Creates a new column with the given title.
+    ///
+    /// `text` returns the content of this column for a row.
+    pub fn new<S, F>(title: S, text: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&T) -> String + 'static,
+    {
+        let title = title.into();
+        TableColumn {
+            content_width: title_width(&title),
+            title,
+            text: Box::new(text),
+            cmp: None,
+            align: HAlign::Left,
+            width: SizeConstraint::Free,
+            size: 0,
+        }
+    }
+
+    /// Sets the alignment of this column.
+    pub fn align(mut self, align: HAlign) -> Self {
+        self.align = align;
+
+        self
+    }
+
+    /// Sets the width constraint of this column.
+    ///
+    /// `Free` columns are as wide as their content, and `Full` columns
+    /// share the space left.
+    pub fn width(mut self, width: SizeConstraint) -> Self {
+        self.width = width;
+
+        self
+    }
+
+    /// Sets the comparison used to sort rows by this column.
+    ///
+    /// By default, rows are compared with the text of this column.
+    pub fn sort_by<F>(mut self, cmp: F) -> Self
+    where
+        F: Fn(&T, &T) -> Ordering + 'static,
+    {
+        self.cmp = Some(Box::new(cmp));
+
+        self
+    }
+
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        match self.cmp {
+            Some(ref cmp) => cmp(a, b),
+            None => (self.text)(a).cmp(&(self.text)(b)),
+        }
+    }
+
+    fn draw_text(&self, printer: &Printer, text: &str) {
+        let x = self.align.get_offset(text.width(), printer.size.x);
+        printer.print((x, 0), text);
+    }
+}
+
+// Returns the width needed to show a column title.
+fn title_width(title: &str) -> usize {
+    // Leave room for the sort indicator.
+    title.width() + 2
+}
+
+/// View to select a row in a table.
+///
+/// Each row is a value of type `T`, and each column shows some text
+/// computed from it.
+///
+/// Clicking on a column title, or pressing `<Enter>` on it, sorts the
+/// rows by this column. Titles are reached with `<Up>` from the first
+/// row. Columns are resized by dragging the separator between two titles,
+/// or with `<Shift+Left>` and `<Shift+Right>` on a title.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate cursive;
+/// # use cursive::Cursive;
+/// # use cursive::align::HAlign;
+/// # use cursive::view::SizeConstraint;
+/// # use cursive::views::{Dialog, TableColumn, TableView};
+/// # fn main() {
+/// struct Fruit {
+///     name: &'static str,
+///     price: u32,
+/// }
+///
+/// let table = TableView::new()
+///     .column(
+///         TableColumn::new("Name", |f: &Fruit| f.name.to_string())
+///             .width(SizeConstraint::Full),
+///     )
+///     .column(
+///         TableColumn::new("Price", |f: &Fruit| f.price.to_string())
+///             .align(HAlign::Right)
+///             .sort_by(|a, b| a.price.cmp(&b.price)),
+///     )
+///     .item(Fruit { name: "Apple", price: 3 })
+///     .item(Fruit { name: "Banana", price: 2 })
+///     .on_submit(|s, fruit: &Fruit| {
+///         s.root_mut()
+///             .add_layer(Dialog::info(format!("{} it is!", fruit.name)));
+///     });
+///
+/// let mut siv = Cursive::new();
+/// siv.root_mut().add_layer(Dialog::around(table).title("Fruits"));
+/// # }
+/// ```
+pub struct TableView<T> {
+    columns: Vec<TableColumn<T>>,
+    items: Vec<Rc<T>>,
+    enabled: bool,
+    focus: usize,
+    // Column selected in the header, if the header has the focus.
+    header_focus: Option<usize>,
+    sort: Option<(usize, SortOrder)>,
+    // Column being resized with the mouse, and where its separator was
+    // grabbed (from the end of the column).
+    resizing: Option<(usize, usize)>,
+    scrollbase: ScrollBase,
+    // First cell of each column, computed during layout.
+    column_starts: Vec<usize>,
+    // It will be called whenever "Enter" is pressed.
+    on_submit: Option<Rc<Fn(&mut Cursive, &T)>>,
+    // This callback is called when the selection is changed.
+    on_select: Option<Rc<Fn(&mut Cursive, &T)>>,
+    last_size: Vec2,
+}
+
+impl<T: 'static> Default for TableView<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> TableView<T> {
+    /// Creates a new empty TableView.
+    pub fn new() -> Self {
+        TableView {
+            columns: Vec::new(),
+            items: Vec::new(),
+            enabled: true,
+            focus: 0,
+            header_focus: None,
+            sort: None,
+            resizing: None,
+            scrollbase: ScrollBase::new(),
+            column_starts: Vec::new(),
+            on_submit: None,
+            on_select: None,
+            last_size: Vec2::zero(),
+        }
+    }
+
+    impl_enabled!(self.enabled);
+
+    /// Adds a column to the right of the table.
+    pub fn add_column(&mut self, mut column: TableColumn<T>) {
+        column.content_width = self.items
+            .iter()
+            .map(|item| (column.text)(item).width())
+            .fold(column.content_width, max);
+        self.columns.push(column);
+    }
+
+    /// Adds a column to the right of the table.
+    ///
+    /// Chainable variant.
+    pub fn column(self, column: TableColumn<T>) -> Self {
+        self.with(|s| s.add_column(column))
+    }
+
+    /// Sets the width constraint of the given column.
+    ///
+    /// # Panics
+    ///
+    /// If `column` is out of bounds.
+    pub fn set_column_width(&mut self, column: usize, width: SizeConstraint) {
+        self.columns[column].width = width;
+    }
+
+    /// Sets a callback to be used when a row is selected.
+    pub fn set_on_select<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, &T) + 'static,
+    {
+        self.on_select = Some(Rc::new(cb));
+    }
+
+    /// Sets a callback to be used when a row is selected.
+    ///
+    /// Chainable variant.
+    pub fn on_select<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &T) + 'static,
+    {
+        self.with(|s| s.set_on_select(cb))
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed,
+    /// or when a row is double-clicked.
+    ///
+    /// The row currently selected will be given to the callback.
+    ///
+    /// Here, `V` can be `T` itself, or a type that can be borrowed from `T`.
+    pub fn set_on_submit<F, R, V: ?Sized>(&mut self, cb: F)
+    where
+        F: 'static + Fn(&mut Cursive, &V) -> R,
+        T: Borrow<V>,
+    {
+        self.on_submit = Some(Rc::new(move |s, t| {
+            cb(s, t.borrow());
+        }));
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed,
+    /// or when a row is double-clicked.
+    ///
+    /// The row currently selected will be given to the callback.
+    ///
+    /// Chainable variant.
+    pub fn on_submit<F, V: ?Sized>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &V) + 'static,
+        T: Borrow<V>,
+    {
+        self.with(|s| s.set_on_submit(cb))
+    }
+
+    /// Returns the value of the currently selected row.
+    ///
+    /// Panics if the table is empty.
+    pub fn selection(&self) -> Rc<T> {
+        Rc::clone(&self.items[self.focus])
+    }
+
+    /// Returns the id of the row currently selected.
+    ///
+    /// Returns `None` if the table is empty.
+    pub fn selected_id(&self) -> Option<usize> {
+        if self.items.is_empty() {
+            None
+        } else {
+            Some(self.focus)
+        }
+    }
+
+    /// Removes all rows from this view.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.focus = 0;
+        self.update_content_widths();
+    }
+
+    /// Adds a row to the table.
+    ///
+    /// If the table is sorted, the row is placed accordingly.
+    pub fn add_item(&mut self, value: T) {
+        let start = self.items.len();
+        self.items.push(Rc::new(value));
+        self.fit_rows(start);
+        self.sort_items();
+    }
+
+    /// Chainable variant of add_item
+    pub fn item(self, value: T) -> Self {
+        self.with(|s| s.add_item(value))
+    }
+
+    /// Adds all rows from an iterator.
+    pub fn add_all<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let start = self.items.len();
+        self.items.extend(iter.into_iter().map(Rc::new));
+        self.fit_rows(start);
+        self.sort_items();
+    }
+
+    /// Adds all rows from an iterator.
+    ///
+    /// Chainable variant.
+    pub fn with_all<I>(self, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.with(|s| s.add_all(iter))
+    }
+
+    /// Gets the row at given idx or None.
+    pub fn get_item(&self, i: usize) -> Option<&T> {
+        self.items.get(i).map(|item| &**item)
+    }
+
+    /// Removes a row from the table.
+    ///
+    /// Returns a callback in response to the selection change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn remove_item(&mut self, id: usize) -> Callback {
+        self.items.remove(id);
+        if self.focus >= id && self.focus > 0 {
+            self.focus -= 1;
+        }
+        self.update_content_widths();
+
+        self.make_select_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Returns the number of rows in this table.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if this table has no row.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Moves the selection to the given row.
+    ///
+    /// Returns a callback in response to the selection change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn set_selection(&mut self, i: usize) -> Callback {
+        self.focus = min(i, self.items.len().saturating_sub(1));
+        self.scrollbase.scroll_to(self.focus);
+
+        self.make_select_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Sets the selection to the given row.
+    ///
+    /// Chainable variant.
+    ///
+    /// Does not apply `on_select` callbacks.
+    pub fn selected(self, i: usize) -> Self {
+        self.with(|s| {
+            s.set_selection(i);
+        })
+    }
+
+    /// Moves the selection up by the given number of rows.
+    ///
+    /// Returns a callback in response to the selection change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn select_up(&mut self, n: usize) -> Callback {
+        let focus = self.focus.saturating_sub(n);
+        self.set_selection(focus)
+    }
+
+    /// Moves the selection down by the given number of rows.
+    ///
+    /// Returns a callback in response to the selection change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    pub fn select_down(&mut self, n: usize) -> Callback {
+        let focus = self.focus + n;
+        self.set_selection(focus)
+    }
+
+    /// Sorts the rows by the given column.
+    ///
+    /// The selected row stays selected. Rows added later are kept sorted.
+    ///
+    /// # Panics
+    ///
+    /// If `column` is out of bounds.
+    pub fn sort_by(&mut self, column: usize, order: SortOrder) {
+        assert!(column < self.columns.len());
+        self.sort = Some((column, order));
+        self.sort_items();
+    }
+
+    /// Sorts the rows by the given column.
+    ///
+    /// Chainable variant.
+    pub fn sorted_by(self, column: usize, order: SortOrder) -> Self {
+        self.with(|s| s.sort_by(column, order))
+    }
+
+    /// Returns the column used to sort the rows, and in which order.
+    ///
+    /// Returns `None` if the rows are not sorted.
+    pub fn sort_order(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    fn sort_items(&mut self) {
+        let (column, order) = match self.sort {
+            Some(sort) => sort,
+            None => return,
+        };
+        let selected = self.items.get(self.focus).cloned();
+
+        let column = &self.columns[column];
+        self.items.sort_by(|a, b| match order {
+            SortOrder::Ascending => column.compare(a, b),
+            SortOrder::Descending => column.compare(b, a),
+        });
+
+        if let Some(selected) = selected {
+            if let Some(i) =
+                self.items.iter().position(|item| Rc::ptr_eq(item, &selected))
+            {
+                self.focus = i;
+                self.scrollbase.scroll_to(i);
+            }
+        }
+    }
+
+    // Sorts by the given column, reversing the order if it already was.
+    fn toggle_sort(&mut self, column: usize) {
+        let order = match self.sort {
+            Some((c, SortOrder::Ascending)) if c == column => {
+                SortOrder::Descending
+            }
+            _ => SortOrder::Ascending,
+        };
+        self.sort_by(column, order);
+    }
+
+    fn resize_column(&mut self, column: usize, width: usize) {
+        self.columns[column].width = SizeConstraint::Fixed(max(1, width));
+    }
+
+    // Widens the columns to fit the rows starting at `start`.
+    fn fit_rows(&mut self, start: usize) {
+        let items = &self.items[start..];
+        for column in &mut self.columns {
+            column.content_width = items
+                .iter()
+                .map(|item| (column.text)(item).width())
+                .fold(column.content_width, max);
+        }
+    }
+
+    // Computes again the width needed by each column, after rows were
+    // removed.
+    fn update_content_widths(&mut self) {
+        for column in &mut self.columns {
+            column.content_width = title_width(&column.title);
+        }
+        self.fit_rows(0);
+    }
+
+    // Returns the width requested by each column.
+    fn requested_widths(&self) -> Vec<usize> {
+        self.columns
+            .iter()
+            .map(|column| {
+                let content = column.content_width;
+                match column.width {
+                    SizeConstraint::Full => content,
+                    width => width.result((content, content)),
+                }
+            })
+            .collect()
+    }
+
+    fn separators_width(&self) -> usize {
+        SEPARATOR_WIDTH * self.columns.len().saturating_sub(1)
+    }
+
+    // Shares `width` cells between the columns.
+    fn layout_columns(&mut self, width: usize) {
+        let available = width.saturating_sub(self.separators_width());
+        let mut sizes = self.requested_widths();
+        let mut total: usize = sizes.iter().sum();
+
+        // Shrink the widest columns first, and fixed ones last.
+        while total > available {
+            let widest = (0..sizes.len())
+                .filter(|&i| sizes[i] > 1)
+                .max_by_key(|&i| match self.columns[i].width {
+                    SizeConstraint::Fixed(_) => (false, sizes[i]),
+                    _ => (true, sizes[i]),
+                });
+            match widest {
+                Some(i) => sizes[i] -= 1,
+                None => break,
+            }
+            total -= 1;
+        }
+
+        // Full columns share what's left.
+        let full: Vec<usize> = self.columns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, column)| match column.width {
+                SizeConstraint::Full => Some(i),
+                _ => None,
+            })
+            .collect();
+        if total < available && !full.is_empty() {
+            let extra = available - total;
+            for (n, &i) in full.iter().enumerate() {
+                sizes[i] += extra / full.len();
+                if n < extra % full.len() {
+                    sizes[i] += 1;
+                }
+            }
+        }
+
+        let mut x = 0;
+        self.column_starts.clear();
+        for (column, size) in self.columns.iter_mut().zip(sizes) {
+            column.size = size;
+            self.column_starts.push(x);
+            x += size + SEPARATOR_WIDTH;
+        }
+    }
+
+    // Returns the column at the given position, if any.
+    fn column_at(&self, x: usize) -> Option<usize> {
+        self.column_starts
+            .iter()
+            .zip(&self.columns)
+            .position(|(&start, column)| {
+                x >= start && x < start + column.size
+            })
+    }
+
+    // Returns the column left of the separator at the given position.
+    fn separator_at(&self, x: usize) -> Option<usize> {
+        let last = self.columns.len().saturating_sub(1);
+        self.column_starts
+            .iter()
+            .zip(&self.columns)
+            .take(last)
+            .position(|(&start, column)| {
+                let end = start + column.size;
+                x >= end && x < end + SEPARATOR_WIDTH
+            })
+    }
+
+    fn draw_header(&self, printer: &Printer) {
+        let separator = printer.box_drawing("│", "|");
+        let starts = &self.column_starts;
+
+        for (i, (column, &x)) in self.columns.iter().zip(starts).enumerate() {
+            if i > 0 {
+                printer.print((x - SEPARATOR_WIDTH + 1, 0), separator);
+            }
+
+            let indicator = match self.sort {
+                Some((c, SortOrder::Ascending)) if c == i => {
+                    printer.box_drawing(" ▲", " ^")
+                }
+                Some((c, SortOrder::Descending)) if c == i => {
+                    printer.box_drawing(" ▼", " v")
+                }
+                _ => "",
+            };
+            let title = format!("{}{}", column.title, indicator);
+
+            let printer = printer.sub_printer((x, 0), (column.size, 1), true);
+            if self.header_focus == Some(i) {
+                printer.with_selection(true, |printer| {
+                    printer.print_hline((0, 0), printer.size.x, " ");
+                    column.draw_text(printer, &title);
+                });
+            } else {
+                printer.with_color(ColorStyle::title_primary(), |printer| {
+                    column.draw_text(printer, &title);
+                });
+            }
+        }
+
+        printer.print_hline(
+            (0, 1),
+            printer.size.x,
+            printer.box_drawing("─", "-"),
+        );
+        for &x in starts.iter().skip(1) {
+            printer.print(
+                (x - SEPARATOR_WIDTH + 1, 1),
+                printer.box_drawing("┼", "+"),
+            );
+        }
+    }
+
+    fn draw_row(&self, printer: &Printer, i: usize) {
+        let separator = printer.box_drawing("│", "|");
+        printer.print_hline((0, 0), printer.size.x, " ");
+
+        let item = &self.items[i];
+        for (column, &x) in self.columns.iter().zip(&self.column_starts) {
+            if x > 0 {
+                printer.print((x - SEPARATOR_WIDTH + 1, 0), separator);
+            }
+            let printer = printer.sub_printer((x, 0), (column.size, 1), true);
+            column.draw_text(&printer, &(column.text)(item));
+        }
+    }
+
+    fn submit(&mut self) -> EventResult {
+        let cb = self.on_submit.clone().unwrap();
+        let v = self.selection();
+        // We return a Callback Rc<|s| cb(s, &*v)>
+        EventResult::Consumed(Some(Callback::from_fn(move |s| cb(s, &v))))
+    }
+
+    /// Returns a callback from selection change.
+    fn make_select_cb(&self) -> Option<Callback> {
+        let v = match self.items.get(self.focus) {
+            Some(item) => Rc::clone(item),
+            None => return None,
+        };
+        self.on_select
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, &v)))
+    }
+
+    // Returns the position in the rows area, if it's not on the scrollbar.
+    fn row_position(&self, position: Vec2) -> Option<Vec2> {
+        let position = position.checked_sub((0, HEADER_HEIGHT))?;
+        let scrollbar_size = if self.scrollbase.scrollable() {
+            (1 + self.scrollbase.right_padding, 0)
+        } else {
+            (0, 0)
+        };
+        let clickable_size = self.last_size
+            .saturating_sub(scrollbar_size)
+            .saturating_sub((0, HEADER_HEIGHT));
+        if position < clickable_size {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    fn on_header_event(&mut self, column: usize, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Left) if column > 0 => {
+                self.header_focus = Some(column - 1);
+            }
+            Event::Key(Key::Right) if column + 1 < self.columns.len() => {
+                self.header_focus = Some(column + 1);
+            }
+            Event::Key(Key::Enter) => self.toggle_sort(column),
+            Event::ModKey(Modifiers::SHIFT, Key::Left) => {
+                let width = self.columns[column].size.saturating_sub(1);
+                self.resize_column(column, width);
+            }
+            Event::ModKey(Modifiers::SHIFT, Key::Right) => {
+                let width = self.columns[column].size + 1;
+                self.resize_column(column, width);
+            }
+            Event::Key(Key::Down) | Event::Key(Key::Esc)
+                if !self.items.is_empty() =>
+            {
+                self.header_focus = None;
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+
+    fn on_rows_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Up) if self.focus > 0 => self.focus -= 1,
+            Event::Key(Key::Up) if !self.columns.is_empty() => {
+                let column = self.sort.map_or(0, |(column, _)| column);
+                self.header_focus = Some(column);
+                return EventResult::Consumed(None);
+            }
+            Event::Key(Key::Down) if self.focus + 1 < self.items.len() => {
+                self.focus += 1
+            }
+            Event::Key(Key::PageUp) => {
+                self.focus = self.focus.saturating_sub(10)
+            }
+            Event::Key(Key::PageDown) => {
+                self.focus =
+                    min(self.focus + 10, self.items.len().saturating_sub(1))
+            }
+            Event::Key(Key::Home) => self.focus = 0,
+            Event::Key(Key::End) => {
+                self.focus = self.items.len().saturating_sub(1)
+            }
+            Event::Key(Key::Enter)
+                if self.on_submit.is_some() && !self.items.is_empty() =>
+            {
+                return self.submit();
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        self.scrollbase.scroll_to(self.focus);
+        EventResult::Consumed(self.make_select_cb())
+    }
+
+    fn on_mouse_event(
+        &mut self, event: MouseEvent, position: Vec2, offset: Vec2
+    ) -> EventResult {
+        let rows_offset = offset + (0, HEADER_HEIGHT);
+        match event {
+            MouseEvent::WheelDown if self.scrollbase.can_scroll_down() => {
+                self.scrollbase.scroll_down(5);
+            }
+            MouseEvent::WheelUp if self.scrollbase.can_scroll_up() => {
+                self.scrollbase.scroll_up(5);
+            }
+            MouseEvent::Press(MouseButton::Left)
+                if position
+                    .checked_sub(offset)
+                    .map(|p| p.y == 0 && p.x < self.last_size.x)
+                    .unwrap_or(false) =>
+            {
+                let x = position.x - offset.x;
+                if let Some(column) = self.separator_at(x) {
+                    let end = self.column_starts[column]
+                        + self.columns[column].size;
+                    self.resizing = Some((column, x - end));
+                } else if let Some(column) = self.column_at(x) {
+                    self.toggle_sort(column);
+                } else {
+                    return EventResult::Ignored;
+                }
+            }
+            MouseEvent::Press(MouseButton::Left)
+                if position
+                    .checked_sub(rows_offset)
+                    .map(|position| {
+                        self.scrollbase.start_drag(position, self.last_size.x)
+                    })
+                    .unwrap_or(false) =>
+            {
+                // The scrollbar was grabbed.
+            }
+            MouseEvent::Press(_) => {
+                let row = position
+                    .checked_sub(offset)
+                    .and_then(|position| self.row_position(position))
+                    .map(|position| position.y + self.scrollbase.start_line)
+                    .filter(|&row| row < self.items.len());
+                match row {
+                    Some(row) => {
+                        self.focus = row;
+                        self.header_focus = None;
+                        return EventResult::Consumed(self.make_select_cb());
+                    }
+                    None => return EventResult::Ignored,
+                }
+            }
+            MouseEvent::Hold(MouseButton::Left) => {
+                // If the mouse is dragged, we always consume the event.
+                match self.resizing {
+                    Some((column, grab)) => {
+                        // Keep the grabbed separator cell under the mouse.
+                        let start = self.column_starts[column] + offset.x;
+                        let width = position.x.saturating_sub(start + grab);
+                        self.resize_column(column, width);
+                    }
+                    None => {
+                        let position = position.saturating_sub(rows_offset);
+                        self.scrollbase.drag(position);
+                    }
+                }
+            }
+            MouseEvent::Release(MouseButton::Left) => {
+                self.resizing = None;
+                self.scrollbase.release_grab();
+            }
+            MouseEvent::Click(MouseButton::Left, 2)
+                if self.on_submit.is_some() =>
+            {
+                let row = position
+                    .checked_sub(offset)
+                    .and_then(|position| self.row_position(position))
+                    .map(|position| position.y + self.scrollbase.start_line);
+                if row == Some(self.focus) && !self.items.is_empty() {
+                    return self.submit();
+                }
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+}
+
+impl<T: 'static> View for TableView<T> {
+    fn draw(&self, printer: &Printer) {
+        let header_focused = self.header_focus.is_some();
+        self.draw_header(&printer.sub_printer(
+            (0, 0),
+            (printer.size.x, HEADER_HEIGHT),
+            header_focused,
+        ));
+
+        let printer = &printer.sub_printer(
+            (0, HEADER_HEIGHT),
+            printer.size.saturating_sub((0, HEADER_HEIGHT)),
+            !header_focused,
+        );
+        self.scrollbase.draw(printer, |printer, i| {
+            if i == self.focus {
+                printer.with_selection(true, |printer| {
+                    self.draw_row(printer, i)
+                });
+            } else if !self.enabled {
+                printer.with_color(ColorStyle::secondary(), |printer| {
+                    self.draw_row(printer, i)
+                });
+            } else if printer.is_hovered() {
+                let style = ColorStyle::highlight_inactive();
+                printer.with_color(style, |printer| self.draw_row(printer, i));
+            } else {
+                self.draw_row(printer, i);
+            }
+        });
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        // Like in `SelectView`, rows are not compressible.
+        let w = self.requested_widths().iter().sum::<usize>()
+            + self.separators_width();
+        let h = self.items.len() + HEADER_HEIGHT;
+
+        let scrolling = req.y < h;
+
+        // Add 2 spaces for the scrollbar if we need
+        let w = if scrolling { w + 2 } else { w };
+
+        Vec2::new(w, min(h, req.y))
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if !self.enabled {
+            return EventResult::Ignored;
+        }
+
+        match (event, self.header_focus) {
+            (
+                Event::Mouse {
+                    event,
+                    position,
+                    offset,
+                },
+                _,
+            ) => self.on_mouse_event(event, position, offset),
+            (event, Some(column)) => self.on_header_event(column, event),
+            (event, None) => self.on_rows_event(event),
+        }
+    }
+
+    fn take_focus(&mut self, _: Direction) -> bool {
+        self.enabled && !self.items.is_empty()
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.last_size = size;
+
+        let height = size.y.saturating_sub(HEADER_HEIGHT);
+        self.scrollbase.set_heights(height, self.items.len());
+
+        let width = if self.scrollbase.scrollable() {
+            size.x.saturating_sub(1 + self.scrollbase.right_padding)
+        } else {
+            size.x
+        };
+        self.layout_columns(width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use backend::puppet::Puppet;
+    use utils::snapshot::{fixture, Snapshot};
+    use view::Identifiable;
+
+    type Fruit = (&'static str, u32);
+
+    fn table() -> TableView<Fruit> {
+        TableView::new()
+            .column(TableColumn::new("Name", |f: &Fruit| f.0.to_string()))
+            .column(
+                TableColumn::new("Qty", |f: &Fruit| f.1.to_string())
+                    .sort_by(|a, b| a.1.cmp(&b.1)),
+            )
+            .with_all(vec![("pear", 10), ("apple", 9), ("fig", 100)])
+    }
+
+    fn names(table: &TableView<Fruit>) -> Vec<&'static str> {
+        table.items.iter().map(|item| item.0).collect()
+    }
+
+    #[test]
+    fn sort_keeps_selection() {
+        let mut table = table().selected(1);
+
+        table.sort_by(0, SortOrder::Ascending);
+        assert_eq!(names(&table), vec!["apple", "fig", "pear"]);
+        assert_eq!(table.selected_id(), Some(0));
+
+        table.sort_by(1, SortOrder::Descending);
+        assert_eq!(names(&table), vec!["fig", "pear", "apple"]);
+        assert_eq!(table.selected_id(), Some(2));
+
+        table.add_item(("kiwi", 50));
+        assert_eq!(names(&table), vec!["fig", "kiwi", "pear", "apple"]);
+        assert_eq!(table.selection().0, "apple");
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut table = TableView::<(String, usize)>::new()
+            .column(
+                TableColumn::new("Name", |row: &(String, usize)| row.0.clone())
+                    .width(SizeConstraint::Full),
+            )
+            .column(
+                TableColumn::new("Size", |row: &(String, usize)| {
+                    row.1.to_string()
+                }).align(HAlign::Right),
+            )
+            .with_all((1..10).map(|i| (format!("Item {}", i), i * i)))
+            .selected(1);
+
+        Snapshot::render(&mut table, (20, 5))
+            .annotated()
+            .assert_matches(fixture("table"));
+    }
+
+    #[test]
+    fn content_width_follows_rows() {
+        let mut table = table();
+        assert_eq!(table.requested_widths(), vec![6, 5]);
+
+        table.add_item(("watermelon", 1));
+        assert_eq!(table.requested_widths(), vec![10, 5]);
+
+        table.remove_item(3);
+        assert_eq!(table.requested_widths(), vec![6, 5]);
+
+        table.clear();
+        table.add_all(vec![("kumquat", 12345)]);
+        assert_eq!(table.requested_widths(), vec![7, 5]);
+    }
+
+    #[test]
+    fn selection_before_layout() {
+        // Every row fits: none is scrolled out.
+        let mut fits = table().selected(1);
+        fits.layout(Vec2::new(20, 10));
+        assert_eq!(fits.scrollbase.start_line, 0);
+
+        // Only two rows fit: the selection is the last one shown.
+        let mut scrolled = table().selected(2);
+        scrolled.layout(Vec2::new(20, 4));
+        assert_eq!(scrolled.scrollbase.start_line, 1);
+    }
+
+    #[test]
+    fn mouse_sort_and_resize() {
+        let backend = Puppet::new((30, 6));
+        let input = backend.input();
+
+        let mut siv = Cursive::with_backend(backend);
+        siv.root_mut()
+            .add_fullscreen_layer(table().with_id("table"));
+        let click = |event, position| Event::Mouse {
+            event,
+            position,
+            offset: Vec2::zero(),
+        };
+
+        // "Name" uses cells 0 to 5, and "Qty" cells 9 to 13. The separator
+        // is grabbed on its last cell, 2 cells after "Name".
+        input.borrow_mut().extend(vec![
+            click(MouseEvent::Press(MouseButton::Left), Vec2::new(11, 0)),
+            click(MouseEvent::Release(MouseButton::Left), Vec2::new(11, 0)),
+            click(MouseEvent::Press(MouseButton::Left), Vec2::new(11, 0)),
+            click(MouseEvent::Release(MouseButton::Left), Vec2::new(11, 0)),
+            click(MouseEvent::Press(MouseButton::Left), Vec2::new(8, 0)),
+            click(MouseEvent::Hold(MouseButton::Left), Vec2::new(12, 0)),
+            click(MouseEvent::Release(MouseButton::Left), Vec2::new(12, 0)),
+        ]);
+        for _ in 0..7 {
+            siv.step();
+        }
+
+        siv.call_on_id("table", |table: &mut TableView<Fruit>| {
+            assert_eq!(table.sort_order(), Some((1, SortOrder::Descending)));
+            assert_eq!(names(table), vec!["fig", "pear", "apple"]);
+            assert_eq!(table.columns[0].size, 10);
+        });
+    }
+
+    #[test]
+    fn header_keyboard() {
+        let mut table = table();
+        table.layout(Vec2::new(30, 6));
+
+        table.on_event(Event::Key(Key::Up));
+        assert_eq!(table.header_focus, Some(0));
+        table.on_event(Event::Key(Key::Right));
+        table.on_event(Event::Key(Key::Enter));
+        assert_eq!(names(&table), vec!["apple", "pear", "fig"]);
+
+        table.on_event(Event::ModKey(Modifiers::SHIFT, Key::Right));
+        table.layout(Vec2::new(30, 6));
+        assert_eq!(table.columns[1].size, 6);
+
+        table.on_event(Event::Key(Key::Down));
+        assert_eq!(table.header_focus, None);
+    }
+}
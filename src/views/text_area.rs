@@ -1,6 +1,8 @@
 use {Printer, With, XY};
+use backend::CursorShape;
 use direction::Direction;
-use event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use event::{Event, EventResult, Key, Modifiers, MouseButton,
+            MouseEvent};
 use std::cmp::min;
 use theme::{ColorStyle, Effect};
 use unicode_segmentation::UnicodeSegmentation;
@@ -332,12 +334,16 @@ impl TextArea {
     }
 
     fn insert(&mut self, ch: char) {
+        self.insert_str(ch.encode_utf8(&mut [0; 4]));
+    }
+
+    fn insert_str(&mut self, text: &str) {
         // First, we inject the data, but keep the cursor unmoved
-        // (So the cursor is to the left of the injected char)
-        self.content.insert(self.cursor, ch);
+        // (So the cursor is to the left of the injected text)
+        self.content.insert_str(self.cursor, text);
 
         // Then, we shift the indexes of every row after this one.
-        let shift = ch.len_utf8();
+        let shift = text.len();
 
         // The current row grows, every other is just shifted.
         let selected_row = self.selected_row();
@@ -475,16 +481,20 @@ impl View for TextArea {
 
                 if printer.focused && i == self.selected_row() {
                     let cursor_offset = self.cursor - row.start;
-                    let c = if cursor_offset == text.len() {
-                        "_"
-                    } else {
-                        text[cursor_offset..]
-                            .graphemes(true)
-                            .next()
-                            .expect("Found no char!")
-                    };
                     let offset = text[..cursor_offset].width();
-                    printer.print((offset, 0), c);
+                    if printer.capabilities().cursor {
+                        printer.set_cursor((offset, 0), CursorShape::Block);
+                    } else {
+                        let c = if cursor_offset == text.len() {
+                            "_"
+                        } else {
+                            text[cursor_offset..]
+                                .graphemes(true)
+                                .next()
+                                .expect("Found no char!")
+                        };
+                        printer.print((offset, 0), c);
+                    }
                 }
             });
         });
@@ -494,6 +504,7 @@ impl View for TextArea {
         let mut fix_scroll = true;
         match event {
             Event::Char(ch) => self.insert(ch),
+            Event::Paste(ref text) => self.insert_str(text),
             Event::Key(Key::Enter) => self.insert('\n'),
             Event::Key(Key::Backspace) if self.cursor > 0 => self.backspace(),
             Event::Key(Key::Del) if self.cursor < self.content.len() => {
@@ -509,8 +520,10 @@ impl View for TextArea {
                     self.move_left();
                 }
             }
-            Event::Ctrl(Key::Home) => self.cursor = 0,
-            Event::Ctrl(Key::End) => self.cursor = self.content.len(),
+            Event::ModKey(Modifiers::CTRL, Key::Home) => self.cursor = 0,
+            Event::ModKey(Modifiers::CTRL, Key::End) => {
+                self.cursor = self.content.len()
+            }
             Event::Key(Key::Home) => {
                 self.cursor = self.rows[self.selected_row()].start
             }
@@ -605,3 +618,22 @@ impl View for TextArea {
         self.compute_rows(size);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste() {
+        let mut area = TextArea::new().content("ab");
+        area.layout(Vec2::new(10, 5));
+        area.set_cursor(1);
+
+        let text = String::from("1\n2\n3");
+        assert!(area.on_event(Event::Paste(text)).is_consumed());
+
+        assert_eq!(area.get_content(), "a1\n2\n3b");
+        assert_eq!(area.cursor(), 6);
+        assert_eq!(area.rows.len(), 3);
+    }
+}
@@ -0,0 +1,879 @@
+use Cursive;
+use Printer;
+use With;
+use direction::Direction;
+use event::{Callback, Event, EventResult, Key, MouseButton, MouseEvent};
+use std::borrow::Borrow;
+use std::cmp::min;
+use std::rc::Rc;
+use theme::ColorStyle;
+use unicode_width::UnicodeWidthStr;
+use vec::Vec2;
+use view::{ScrollBase, View};
+
+/// Child returned by the loader of a [`TreeView`].
+///
+/// [`TreeView`]: struct.TreeView.html
+pub struct TreeEntry<T> {
+    label: String,
+    value: T,
+    container: bool,
+}
+
+impl<T> TreeEntry<T> {
+    /// Creates an entry without children.
+    pub fn leaf<S: Into<String>>(label: S, value: T) -> Self {
+        TreeEntry {
+            label: label.into(),
+            value,
+            container: false,
+        }
+    }
+
+    /// Creates an entry that can be expanded.
+    ///
+    /// Its children will be loaded when it is first expanded.
+    pub fn container<S: Into<String>>(label: S, value: T) -> Self {
+        TreeEntry {
+            label: label.into(),
+            value,
+            container: true,
+        }
+    }
+}
+
+/// View to select an item in a tree.
+///
+/// Each node has a label and a value of type `T`. Containers can be
+/// expanded to show their children, which are either added directly, or
+/// loaded the first time the container is expanded (see [`set_loader`]).
+///
+/// Nodes are identified by the id returned when they are added.
+///
+/// `<Right>` expands the selected container, or moves to its first child.
+/// `<Left>` collapses it, or moves to the parent. `<Enter>` toggles
+/// containers and submits other nodes.
+///
+/// # Examples
+///
+/// ```no_run
+/// # extern crate cursive;
+/// # use cursive::Cursive;
+/// # use cursive::views::{Dialog, TreeEntry, TreeView};
+/// # use std::fs;
+/// # use std::path::PathBuf;
+/// # fn main() {
+/// let mut tree = TreeView::new().loader(|path: &PathBuf| {
+///     let mut entries = Vec::new();
+///     for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+///         let name = entry.file_name().to_string_lossy().into_owned();
+///         if entry.path().is_dir() {
+///             entries.push(TreeEntry::container(name, entry.path()));
+///         } else {
+///             entries.push(TreeEntry::leaf(name, entry.path()));
+///         }
+///     }
+///     entries
+/// });
+/// tree.add_container(None, "/", PathBuf::from("/"));
+///
+/// let mut siv = Cursive::new();
+/// siv.root_mut().add_layer(Dialog::around(tree).title("Files"));
+/// # }
+/// ```
+///
+/// [`set_loader`]: #method.set_loader
+pub struct TreeView<T> {
+    nodes: Vec<Node<T>>,
+    roots: Vec<usize>,
+    // Ids of the nodes currently shown, in order.
+    visible: Vec<usize>,
+    enabled: bool,
+    // Index of the selected node in `visible`.
+    focus: usize,
+    scrollbase: ScrollBase,
+    loader: Option<Box<Fn(&T) -> Vec<TreeEntry<T>>>>,
+    // It will be called whenever "Enter" is pressed on a leaf.
+    on_submit: Option<Rc<Fn(&mut Cursive, &T)>>,
+    // This callback is called when the selection is changed.
+    on_select: Option<Rc<Fn(&mut Cursive, &T)>>,
+    last_size: Vec2,
+}
+
+impl<T: 'static> Default for TreeView<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: 'static> TreeView<T> {
+    /// Creates a new empty TreeView.
+    pub fn new() -> Self {
+        TreeView {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            visible: Vec::new(),
+            enabled: true,
+            focus: 0,
+            scrollbase: ScrollBase::new(),
+            loader: None,
+            on_submit: None,
+            on_select: None,
+            last_size: Vec2::zero(),
+        }
+    }
+
+    impl_enabled!(self.enabled);
+
+    /// Sets the function loading the children of a container.
+    ///
+    /// It is called once for each container, the first time it is
+    /// expanded, unless children were already added to it.
+    pub fn set_loader<F>(&mut self, loader: F)
+    where
+        F: Fn(&T) -> Vec<TreeEntry<T>> + 'static,
+    {
+        self.loader = Some(Box::new(loader));
+    }
+
+    /// Sets the function loading the children of a container.
+    ///
+    /// Chainable variant.
+    pub fn loader<F>(self, loader: F) -> Self
+    where
+        F: Fn(&T) -> Vec<TreeEntry<T>> + 'static,
+    {
+        self.with(|s| s.set_loader(loader))
+    }
+
+    /// Sets a callback to be used when a node is selected.
+    pub fn set_on_select<F>(&mut self, cb: F)
+    where
+        F: Fn(&mut Cursive, &T) + 'static,
+    {
+        self.on_select = Some(Rc::new(cb));
+    }
+
+    /// Sets a callback to be used when a node is selected.
+    ///
+    /// Chainable variant.
+    pub fn on_select<F>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &T) + 'static,
+    {
+        self.with(|s| s.set_on_select(cb))
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed on a node
+    /// which is not a container, or when it is double-clicked.
+    ///
+    /// The node currently selected will be given to the callback.
+    ///
+    /// Here, `V` can be `T` itself, or a type that can be borrowed from `T`.
+    pub fn set_on_submit<F, R, V: ?Sized>(&mut self, cb: F)
+    where
+        F: 'static + Fn(&mut Cursive, &V) -> R,
+        T: Borrow<V>,
+    {
+        self.on_submit = Some(Rc::new(move |s, t| {
+            cb(s, t.borrow());
+        }));
+    }
+
+    /// Sets a callback to be used when `<Enter>` is pressed on a node
+    /// which is not a container, or when it is double-clicked.
+    ///
+    /// The node currently selected will be given to the callback.
+    ///
+    /// Chainable variant.
+    pub fn on_submit<F, V: ?Sized>(self, cb: F) -> Self
+    where
+        F: Fn(&mut Cursive, &V) + 'static,
+        T: Borrow<V>,
+    {
+        self.with(|s| s.set_on_submit(cb))
+    }
+
+    /// Adds a node without children.
+    ///
+    /// It is added at the end of the children of `parent`, or at the end
+    /// of the top-level nodes if `parent` is `None`.
+    ///
+    /// Returns the id of the new node.
+    ///
+    /// # Panics
+    ///
+    /// If `parent` is not a valid id.
+    pub fn add_item<S: Into<String>>(
+        &mut self, parent: Option<usize>, label: S, value: T
+    ) -> usize {
+        self.add_node(parent, TreeEntry::leaf(label, value))
+    }
+
+    /// Adds a node that can be expanded.
+    ///
+    /// Its children can be added with `add_item` and `add_container`, or
+    /// loaded when it is first expanded.
+    ///
+    /// Returns the id of the new node.
+    ///
+    /// # Panics
+    ///
+    /// If `parent` is not a valid id.
+    pub fn add_container<S: Into<String>>(
+        &mut self, parent: Option<usize>, label: S, value: T
+    ) -> usize {
+        self.add_node(parent, TreeEntry::container(label, value))
+    }
+
+    fn add_node(
+        &mut self, parent: Option<usize>, entry: TreeEntry<T>
+    ) -> usize {
+        let id = self.push_node(parent, entry);
+
+        // The new node is only shown if its parent is expanded, after the
+        // last visible descendant of the parent.
+        let index = match parent {
+            None => Some(self.visible.len()),
+            Some(parent) if self.nodes[parent].expanded => self.visible
+                .iter()
+                .position(|&i| i == parent)
+                .map(|i| self.subtree_end(i)),
+            Some(_) => None,
+        };
+        if let Some(index) = index {
+            if index <= self.focus && !self.visible.is_empty() {
+                self.focus += 1;
+            }
+            self.visible.insert(index, id);
+        }
+
+        id
+    }
+
+    // Returns the index in `visible` following the visible descendants of
+    // the node at `index`.
+    fn subtree_end(&self, index: usize) -> usize {
+        let depth = self.nodes[self.visible[index]].depth;
+        self.visible[index + 1..]
+            .iter()
+            .position(|&i| self.nodes[i].depth <= depth)
+            .map_or(self.visible.len(), |n| index + 1 + n)
+    }
+
+    // Adds a node, without updating the visible nodes.
+    fn push_node(
+        &mut self, parent: Option<usize>, entry: TreeEntry<T>
+    ) -> usize {
+        let id = self.nodes.len();
+        let depth = match parent {
+            Some(parent) => {
+                let parent = &mut self.nodes[parent];
+                parent.container = true;
+                parent.loaded = true;
+                parent.children.push(id);
+                parent.depth + 1
+            }
+            None => {
+                self.roots.push(id);
+                0
+            }
+        };
+        self.nodes.push(Node {
+            label: entry.label,
+            value: Rc::new(entry.value),
+            parent,
+            children: Vec::new(),
+            depth,
+            container: entry.container,
+            loaded: false,
+            expanded: false,
+        });
+
+        id
+    }
+
+    /// Removes all nodes from this view.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.visible.clear();
+        self.focus = 0;
+    }
+
+    /// Returns the value of the given node, if it exists.
+    pub fn get_item(&self, id: usize) -> Option<&T> {
+        self.nodes.get(id).map(|node| &*node.value)
+    }
+
+    /// Returns the parent of the given node, if any.
+    pub fn parent(&self, id: usize) -> Option<usize> {
+        self.nodes.get(id).and_then(|node| node.parent)
+    }
+
+    /// Returns the children of the given node.
+    ///
+    /// Children of containers are empty until they are loaded.
+    pub fn children(&self, id: usize) -> &[usize] {
+        self.nodes.get(id).map_or(&[], |node| &node.children)
+    }
+
+    /// Returns the number of nodes in this tree, including hidden ones.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if this tree has no node.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns `true` if the given node is expanded.
+    pub fn is_expanded(&self, id: usize) -> bool {
+        self.nodes.get(id).map(|node| node.expanded).unwrap_or(false)
+    }
+
+    /// Expands the given container, loading its children if needed.
+    ///
+    /// Does nothing if the node is not a container.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is not a valid id.
+    pub fn expand(&mut self, id: usize) {
+        if !self.nodes[id].container {
+            return;
+        }
+
+        if !self.nodes[id].loaded {
+            self.nodes[id].loaded = true;
+            let entries = match self.loader {
+                Some(ref loader) => loader(&self.nodes[id].value),
+                None => Vec::new(),
+            };
+            for entry in entries {
+                self.push_node(Some(id), entry);
+            }
+        }
+        self.nodes[id].expanded = true;
+        self.update_visible();
+    }
+
+    /// Collapses the given container, hiding its children.
+    ///
+    /// If a hidden node was selected, the container is selected instead.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is not a valid id.
+    pub fn collapse(&mut self, id: usize) {
+        self.nodes[id].expanded = false;
+        self.update_visible();
+    }
+
+    fn toggle(&mut self, id: usize) {
+        if self.nodes[id].expanded {
+            self.collapse(id);
+        } else {
+            self.expand(id);
+        }
+    }
+
+    /// Returns the value of the currently selected node.
+    ///
+    /// Panics if the tree is empty.
+    pub fn selection(&self) -> Rc<T> {
+        Rc::clone(&self.nodes[self.visible[self.focus]].value)
+    }
+
+    /// Returns the id of the node currently selected.
+    ///
+    /// Returns `None` if the tree is empty.
+    pub fn selected_id(&self) -> Option<usize> {
+        self.visible.get(self.focus).cloned()
+    }
+
+    /// Selects the given node, expanding its parents if needed.
+    ///
+    /// Returns a callback in response to the selection change.
+    ///
+    /// You should run this callback with a `&mut Cursive`.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is not a valid id.
+    pub fn set_selection(&mut self, id: usize) -> Callback {
+        let mut parent = self.nodes[id].parent;
+        while let Some(id) = parent {
+            self.nodes[id].expanded = true;
+            parent = self.nodes[id].parent;
+        }
+        self.update_visible();
+
+        if let Some(focus) = self.visible.iter().position(|&i| i == id) {
+            self.focus = focus;
+            self.scrollbase.scroll_to(focus);
+        }
+
+        self.make_select_cb().unwrap_or_else(Callback::dummy)
+    }
+
+    /// Selects the given node, expanding its parents if needed.
+    ///
+    /// Chainable variant.
+    ///
+    /// Does not apply `on_select` callbacks.
+    ///
+    /// # Panics
+    ///
+    /// If `id` is not a valid id.
+    pub fn selected(self, id: usize) -> Self {
+        self.with(|s| {
+            s.set_selection(id);
+        })
+    }
+
+    // Lists the nodes to show, keeping the selected one if possible.
+    fn update_visible(&mut self) {
+        let mut selected = self.visible.get(self.focus).cloned();
+
+        self.visible.clear();
+        let mut stack: Vec<usize> = self.roots.iter().rev().cloned().collect();
+        while let Some(id) = stack.pop() {
+            self.visible.push(id);
+            let node = &self.nodes[id];
+            if node.expanded {
+                stack.extend(node.children.iter().rev());
+            }
+        }
+
+        // If the selection is now hidden, select its visible ancestor.
+        while let Some(id) = selected {
+            if let Some(focus) = self.visible.iter().position(|&i| i == id) {
+                self.focus = focus;
+                return;
+            }
+            selected = self.nodes[id].parent;
+        }
+        self.focus = min(self.focus, self.visible.len().saturating_sub(1));
+    }
+
+    // Returns `true` if the node is the last one among its siblings.
+    fn is_last(&self, id: usize) -> bool {
+        let siblings = match self.nodes[id].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+        siblings.last() == Some(&id)
+    }
+
+    // Returns the guides and arrow drawn before the label of a node.
+    fn prefix(&self, printer: &Printer, id: usize) -> String {
+        let node = &self.nodes[id];
+
+        // Built from the node up to the root.
+        let mut parts = Vec::new();
+        if node.parent.is_some() {
+            parts.push(if self.is_last(id) {
+                printer.box_drawing("└─", "`-")
+            } else {
+                printer.box_drawing("├─", "|-")
+            });
+
+            let mut ancestor = node.parent;
+            while let Some(id) = ancestor {
+                ancestor = self.nodes[id].parent;
+                if ancestor.is_some() {
+                    parts.push(if self.is_last(id) {
+                        "  "
+                    } else {
+                        printer.box_drawing("│ ", "| ")
+                    });
+                }
+            }
+        }
+        parts.reverse();
+
+        // Every marker takes two cells, so siblings are aligned.
+        let marker = if !node.container {
+            if node.parent.is_some() {
+                printer.box_drawing("─ ", "- ")
+            } else {
+                "  "
+            }
+        } else if node.expanded {
+            printer.box_drawing("▾ ", "- ")
+        } else {
+            printer.box_drawing("▸ ", "+ ")
+        };
+        parts.push(marker);
+
+        parts.concat()
+    }
+
+    // Returns the column of the arrow for the given node.
+    fn arrow_x(&self, id: usize) -> usize {
+        2 * self.nodes[id].depth
+    }
+
+    fn draw_node(&self, printer: &Printer, i: usize) {
+        let id = self.visible[i];
+        let prefix = self.prefix(printer, id);
+        let x = prefix.width();
+
+        printer.print_hline((0, 0), printer.size.x, " ");
+        printer.print((0, 0), &prefix);
+
+        let label = &self.nodes[id].label;
+        let style = if i == self.focus {
+            None
+        } else if !self.enabled {
+            Some(ColorStyle::secondary())
+        } else if printer.is_hovered() {
+            Some(ColorStyle::highlight_inactive())
+        } else {
+            Some(ColorStyle::primary())
+        };
+        let draw = |printer: &Printer| {
+            printer.print_hline((x, 0), printer.size.x, " ");
+            printer.print((x, 0), label);
+        };
+        match style {
+            Some(style) => printer.with_color(style, draw),
+            None => printer.with_selection(true, draw),
+        }
+    }
+
+    fn submit(&mut self) -> EventResult {
+        let cb = self.on_submit.clone().unwrap();
+        let v = self.selection();
+        // We return a Callback Rc<|s| cb(s, &*v)>
+        EventResult::Consumed(Some(Callback::from_fn(move |s| cb(s, &v))))
+    }
+
+    // Expands containers, and submits other nodes.
+    fn activate(&mut self, id: usize) -> EventResult {
+        if self.nodes[id].container {
+            self.toggle(id);
+            EventResult::Consumed(None)
+        } else if self.on_submit.is_some() {
+            self.submit()
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    /// Returns a callback from selection change.
+    fn make_select_cb(&self) -> Option<Callback> {
+        let v = match self.selected_id() {
+            Some(id) => Rc::clone(&self.nodes[id].value),
+            None => return None,
+        };
+        self.on_select
+            .clone()
+            .map(|cb| Callback::from_fn(move |s| cb(s, &v)))
+    }
+
+    // Returns the visible row at the given position, if any.
+    fn row_at(&self, position: Vec2) -> Option<usize> {
+        let scrollbar_size = if self.scrollbase.scrollable() {
+            (1 + self.scrollbase.right_padding, 0)
+        } else {
+            (0, 0)
+        };
+        let clickable_size = self.last_size.saturating_sub(scrollbar_size);
+        if position < clickable_size {
+            Some(position.y + self.scrollbase.start_line)
+                .filter(|&row| row < self.visible.len())
+        } else {
+            None
+        }
+    }
+
+    fn on_key_event(&mut self, key: Key) -> EventResult {
+        let id = match self.selected_id() {
+            Some(id) => id,
+            None => return EventResult::Ignored,
+        };
+        let node = &self.nodes[id];
+
+        match key {
+            Key::Up if self.focus > 0 => self.focus -= 1,
+            Key::Down if self.focus + 1 < self.visible.len() => {
+                self.focus += 1
+            }
+            Key::PageUp => self.focus = self.focus.saturating_sub(10),
+            Key::PageDown => {
+                self.focus =
+                    min(self.focus + 10, self.visible.len().saturating_sub(1))
+            }
+            Key::Home => self.focus = 0,
+            Key::End => self.focus = self.visible.len().saturating_sub(1),
+            Key::Right if node.container && !node.expanded => {
+                self.expand(id);
+                return EventResult::Consumed(None);
+            }
+            Key::Right if !node.children.is_empty() && node.expanded => {
+                self.focus += 1
+            }
+            Key::Left if node.expanded => {
+                self.collapse(id);
+                return EventResult::Consumed(None);
+            }
+            Key::Left if node.parent.is_some() => {
+                let parent = node.parent;
+                self.focus = self.visible
+                    .iter()
+                    .position(|&i| Some(i) == parent)
+                    .unwrap_or(self.focus);
+            }
+            Key::Enter => return self.activate(id),
+            _ => return EventResult::Ignored,
+        }
+
+        self.scrollbase.scroll_to(self.focus);
+        EventResult::Consumed(self.make_select_cb())
+    }
+
+    fn on_mouse_event(
+        &mut self, event: MouseEvent, position: Vec2, offset: Vec2
+    ) -> EventResult {
+        match event {
+            MouseEvent::WheelDown if self.scrollbase.can_scroll_down() => {
+                self.scrollbase.scroll_down(5);
+            }
+            MouseEvent::WheelUp if self.scrollbase.can_scroll_up() => {
+                self.scrollbase.scroll_up(5);
+            }
+            MouseEvent::Press(MouseButton::Left)
+                if position
+                    .checked_sub(offset)
+                    .map(|position| {
+                        self.scrollbase.start_drag(position, self.last_size.x)
+                    })
+                    .unwrap_or(false) =>
+            {
+                // The scrollbar was grabbed.
+            }
+            MouseEvent::Press(btn) => {
+                let position = match position.checked_sub(offset) {
+                    Some(position) => position,
+                    None => return EventResult::Ignored,
+                };
+                let row = match self.row_at(position) {
+                    Some(row) => row,
+                    None => return EventResult::Ignored,
+                };
+
+                let id = self.visible[row];
+                self.focus = row;
+                if btn == MouseButton::Left
+                    && self.nodes[id].container
+                    && position.x == self.arrow_x(id)
+                {
+                    self.toggle(id);
+                }
+                return EventResult::Consumed(self.make_select_cb());
+            }
+            MouseEvent::Hold(MouseButton::Left) => {
+                // If the mouse is dragged, we always consume the event.
+                let position = position.saturating_sub(offset);
+                self.scrollbase.drag(position);
+            }
+            MouseEvent::Release(MouseButton::Left) => {
+                self.scrollbase.release_grab();
+            }
+            MouseEvent::Click(MouseButton::Left, 2) => {
+                let row = position
+                    .checked_sub(offset)
+                    .and_then(|position| self.row_at(position));
+                if row == Some(self.focus) {
+                    let id = self.visible[self.focus];
+                    // The arrow was already handled by the press.
+                    let on_arrow = self.nodes[id].container
+                        && position.x - offset.x == self.arrow_x(id);
+                    if !on_arrow {
+                        return self.activate(id);
+                    }
+                }
+            }
+            _ => return EventResult::Ignored,
+        }
+
+        EventResult::Consumed(None)
+    }
+}
+
+impl<T: 'static> View for TreeView<T> {
+    fn draw(&self, printer: &Printer) {
+        self.scrollbase.draw(printer, |printer, i| self.draw_node(printer, i));
+    }
+
+    fn required_size(&mut self, req: Vec2) -> Vec2 {
+        // Each level adds two cells of guides, and the marker two more.
+        let w = self.visible
+            .iter()
+            .map(|&id| {
+                let node = &self.nodes[id];
+                2 * node.depth + 2 + node.label.width()
+            })
+            .max()
+            .unwrap_or(1);
+        let h = self.visible.len();
+
+        let scrolling = req.y < h;
+
+        // Add 2 spaces for the scrollbar if we need
+        let w = if scrolling { w + 2 } else { w };
+
+        Vec2::new(w, min(h, req.y))
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if !self.enabled {
+            return EventResult::Ignored;
+        }
+
+        match event {
+            Event::Key(key) => self.on_key_event(key),
+            Event::Mouse {
+                event,
+                position,
+                offset,
+            } => self.on_mouse_event(event, position, offset),
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn take_focus(&mut self, _: Direction) -> bool {
+        self.enabled && !self.visible.is_empty()
+    }
+
+    fn layout(&mut self, size: Vec2) {
+        self.last_size = size;
+        self.scrollbase.set_heights(size.y, self.visible.len());
+    }
+}
+
+struct Node<T> {
+    label: String,
+    value: Rc<T>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    depth: usize,
+    container: bool,
+    // `true` once children were added or loaded.
+    loaded: bool,
+    expanded: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use utils::snapshot::{fixture, Snapshot};
+
+    #[test]
+    fn add_to_expanded() {
+        let mut tree = TreeView::new();
+        let a = tree.add_container(None, "a", ());
+        let b = tree.add_container(Some(a), "b", ());
+        tree.expand(a);
+        let c = tree.add_item(Some(b), "c", ());
+        let d = tree.add_item(None, "d", ());
+        tree.set_selection(d);
+
+        // `b` is collapsed: `c` is hidden.
+        assert_eq!(tree.visible, vec![a, b, d]);
+
+        tree.expand(b);
+        let e = tree.add_item(Some(a), "e", ());
+        let f = tree.add_item(Some(b), "f", ());
+        assert_eq!(tree.visible, vec![a, b, c, f, e, d]);
+        assert_eq!(tree.selected_id(), Some(d));
+    }
+
+    #[test]
+    fn lazy_loading() {
+        let loads = Rc::new(Cell::new(0));
+        let mut tree = {
+            let loads = Rc::clone(&loads);
+            TreeView::new().loader(move |&depth: &usize| {
+                loads.set(loads.get() + 1);
+                vec![
+                    TreeEntry::container("dir", depth + 1),
+                    TreeEntry::leaf("file", depth + 1),
+                ]
+            })
+        };
+        let root = tree.add_container(None, "root", 0);
+        assert_eq!(loads.get(), 0);
+
+        tree.expand(root);
+        assert_eq!(loads.get(), 1);
+        assert_eq!(tree.children(root), &[1, 2]);
+
+        tree.collapse(root);
+        tree.expand(root);
+        assert_eq!(loads.get(), 1);
+
+        // Containers with children added directly are not loaded.
+        let other = tree.add_container(None, "other", 0);
+        tree.add_item(Some(other), "child", 1);
+        tree.expand(other);
+        assert_eq!(loads.get(), 1);
+        assert_eq!(tree.visible, vec![root, 1, 2, other, 4]);
+    }
+
+    #[test]
+    fn keyboard_navigation() {
+        let mut tree = TreeView::new();
+        let root = tree.add_container(None, "root", ());
+        let dir = tree.add_container(Some(root), "dir", ());
+        let file = tree.add_item(Some(dir), "file", ());
+        tree.layout(Vec2::new(20, 10));
+
+        let right = Event::Key(Key::Right);
+        let left = Event::Key(Key::Left);
+
+        // Expands, then moves to the first child.
+        tree.on_event(right.clone());
+        assert!(tree.is_expanded(root));
+        assert_eq!(tree.selected_id(), Some(root));
+        tree.on_event(right.clone());
+        assert_eq!(tree.selected_id(), Some(dir));
+        tree.on_event(right.clone());
+        tree.on_event(right.clone());
+        assert_eq!(tree.selected_id(), Some(file));
+
+        // Goes to the parent, then collapses it.
+        tree.on_event(left.clone());
+        assert_eq!(tree.selected_id(), Some(dir));
+        tree.on_event(left.clone());
+        assert!(!tree.is_expanded(dir));
+        assert_eq!(tree.visible, vec![root, dir]);
+
+        // Collapsing an ancestor selects it.
+        tree.set_selection(file);
+        tree.collapse(root);
+        assert_eq!(tree.selected_id(), Some(root));
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut tree = TreeView::new();
+        let src = tree.add_container(None, "src", ());
+        let views = tree.add_container(Some(src), "views", ());
+        tree.add_item(Some(views), "button.rs", ());
+        tree.add_item(Some(views), "dialog.rs", ());
+        tree.add_container(Some(src), "backend", ());
+        tree.add_item(Some(src), "lib.rs", ());
+        tree.add_item(None, "Cargo.toml", ());
+        tree.expand(views);
+        tree.set_selection(views);
+
+        Snapshot::render(&mut tree, (16, 7))
+            .annotated()
+            .assert_matches(fixture("tree"));
+    }
+}
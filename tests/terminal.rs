@@ -0,0 +1,161 @@
+//! Runs terminal backends in a pseudo-terminal.
+//!
+//! Each check runs the ignored `ctrl_z_child` test in a new process, which
+//! fails if the test fails or if the backend exits the process.
+#![cfg(all(unix, feature = "libc"))]
+
+extern crate cursive;
+extern crate libc;
+
+use cursive::Cursive;
+use cursive::backend;
+use cursive::event::{Event, Modifiers};
+use std::cell::Cell;
+use std::env;
+use std::ffi::CStr;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::FromRawFd;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Set when `ctrl_z_child` is started by `spawn`.
+const CHILD_VAR: &str = "CURSIVE_TEST_TERMINAL_CHILD";
+
+/// Opens a new pseudo-terminal of 80x24 cells.
+///
+/// Returns the master side, and the path to the slave side.
+fn open_pty() -> (File, String) {
+    unsafe {
+        let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(fd >= 0, "could not open a pseudo-terminal");
+        let master = File::from_raw_fd(fd);
+        assert_eq!(libc::grantpt(fd), 0);
+        assert_eq!(libc::unlockpt(fd), 0);
+
+        let size = libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        libc::ioctl(fd, libc::TIOCSWINSZ, &size);
+
+        let name = CStr::from_ptr(libc::ptsname(fd));
+        (master, name.to_string_lossy().into_owned())
+    }
+}
+
+/// Starts the given ignored test in a pseudo-terminal.
+///
+/// Returns the child process, and the master side of the terminal.
+fn spawn(test: &str, backend_name: &str) -> (Child, File) {
+    let (master, slave) = open_pty();
+    let slave = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(slave)
+        .unwrap();
+
+    let child = Command::new(env::current_exe().unwrap())
+        .args(&["--ignored", "--exact", test])
+        .env(CHILD_VAR, "1")
+        .env(backend::BACKEND_VAR, backend_name)
+        .env("TERM", "xterm")
+        .stdin(Stdio::from(slave.try_clone().unwrap()))
+        .stdout(Stdio::from(slave))
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Whatever the child draws is ignored, but has to be read.
+    let mut output = master.try_clone().unwrap();
+    thread::spawn(move || {
+        let mut buffer = [0; 1024];
+        while let Ok(n) = output.read(&mut buffer) {
+            if n == 0 {
+                break;
+            }
+        }
+    });
+
+    (child, master)
+}
+
+/// Waits for the child to exit, calling `f` every 50ms meanwhile.
+fn wait<F>(mut child: Child, mut f: F) -> ExitStatus
+where
+    F: FnMut(),
+{
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status;
+        }
+        if start.elapsed() > Duration::from_secs(10) {
+            child.kill().unwrap();
+            panic!("the child process did not exit");
+        }
+        f();
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+/// Checks that Ctrl-Z is read as a key, rather than stopping the process.
+fn check_ctrl_z(backend_name: &str) {
+    let (child, mut master) = spawn("ctrl_z_child", backend_name);
+
+    // The key is lost until the backend is set up, so we keep sending it
+    // until the child exits.
+    let status = wait(child, || {
+        let _ = master.write_all(b"\x1A");
+    });
+    assert!(status.success(), "{} backend missed Ctrl-Z", backend_name);
+}
+
+/// Runs in the pseudo-terminal, started by `check_ctrl_z`.
+#[test]
+#[ignore]
+fn ctrl_z_child() {
+    if env::var_os(CHILD_VAR).is_none() {
+        return;
+    }
+
+    let received = Rc::new(Cell::new(false));
+    {
+        let mut siv = Cursive::try_new().unwrap();
+        let flag = Rc::clone(&received);
+        siv.add_global_callback(
+            Event::ModChar(Modifiers::CTRL, 'z'),
+            move |s| {
+                flag.set(true);
+                s.quit();
+            },
+        );
+        siv.add_timer(Duration::from_secs(5), |s| s.quit());
+        siv.run();
+    }
+    assert!(received.get());
+}
+
+#[test]
+#[cfg(feature = "ncurses")]
+fn ncurses() {
+    check_ctrl_z("ncurses");
+}
+
+#[test]
+#[cfg(feature = "pancurses")]
+fn pancurses() {
+    check_ctrl_z("pancurses");
+}
+
+#[test]
+#[cfg(feature = "termion")]
+fn termion() {
+    check_ctrl_z("termion");
+}